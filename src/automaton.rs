@@ -0,0 +1,149 @@
+//! Running cellular automata round after round, until they settle down or for a fixed number of
+//! rounds.
+
+/// How to compute one round of an automaton. The state can be anything, dense or sparse, as long
+/// as two states can be compared to tell when the automaton has settled.
+pub trait Rule<S> {
+    /// Overwrite `next` with the state following `current`, in the given round (counting from 0)
+    fn step(&mut self, current: &S, next: &mut S, round: usize);
+}
+
+impl<S, F> Rule<S> for F
+where
+    F: FnMut(&S, &mut S, usize),
+{
+    fn step(&mut self, current: &S, next: &mut S, round: usize) {
+        self(current, next, round)
+    }
+}
+
+/// An automaton's state, double-buffered so that each round writes into the state from two rounds
+/// before instead of allocating a new one
+pub struct Automaton<S, R> {
+    current: S,
+    next: S,
+    rule: R,
+    round: usize,
+    settled: bool,
+}
+
+impl<S, R> Automaton<S, R>
+where
+    S: Clone + PartialEq,
+    R: Rule<S>,
+{
+    pub fn new(initial: S, rule: R) -> Self {
+        Self {
+            next: initial.clone(),
+            current: initial,
+            rule,
+            round: 0,
+            settled: false,
+        }
+    }
+
+    pub fn state(&self) -> &S {
+        &self.current
+    }
+
+    /// Run a single round, returning whether it changed anything
+    pub fn step(&mut self) -> bool {
+        self.rule.step(&self.current, &mut self.next, self.round);
+        std::mem::swap(&mut self.current, &mut self.next);
+        self.round += 1;
+        self.settled = self.current == self.next;
+
+        !self.settled
+    }
+
+    /// Run up to `rounds` more rounds, stopping early if the automaton settles. Returns the number
+    /// of rounds actually run.
+    pub fn run_for(&mut self, rounds: usize) -> usize {
+        let start = self.round;
+        while !self.settled && self.round - start < rounds {
+            self.step();
+        }
+
+        self.round - start
+    }
+
+    /// Run until a round changes nothing, returning the total number of rounds run, including that
+    /// last one
+    pub fn run_to_fixed_point(&mut self) -> usize {
+        while !self.settled {
+            self.step();
+        }
+
+        self.round
+    }
+}
+
+/// A list of rules or choices tried in order, where the order rotates by one every round
+#[derive(Debug, Clone)]
+pub struct RotatingPriority<T> {
+    items: Vec<T>,
+}
+
+impl<T> RotatingPriority<T> {
+    pub fn new(items: impl IntoIterator<Item = T>) -> Self {
+        Self {
+            items: items.into_iter().collect(),
+        }
+    }
+
+    /// The items in order of priority for the given round
+    pub fn order(&self, round: usize) -> impl Iterator<Item = &T> {
+        let start = round % self.items.len().max(1);
+        self.items[start..].iter().chain(&self.items[..start])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::grid::BitGrid;
+
+    #[test]
+    fn settles() {
+        // Every square drops one row at a time until it hits the bottom
+        let mut grid = BitGrid::new(3, 4);
+        grid.set((1, 0), true);
+        grid.set((2, 1), true);
+
+        let mut automaton = Automaton::new(grid, |current: &BitGrid, next: &mut BitGrid, _| {
+            let bottom = current.height() - 1;
+            *next = current.shifted((0, 1));
+            for x in 0..current.width() {
+                if current.get((x, bottom)) {
+                    next.set((x, bottom), true);
+                }
+            }
+        });
+
+        assert_eq!(automaton.run_for(2), 2);
+        assert_eq!(automaton.state().ones().collect_vec(), [(1, 2), (2, 3)]);
+        assert_eq!(automaton.run_to_fixed_point(), 4);
+        assert_eq!(automaton.state().ones().collect_vec(), [(1, 3), (2, 3)]);
+        assert_eq!(automaton.run_for(10), 0);
+    }
+
+    #[test]
+    fn oscillates() {
+        let mut automaton = Automaton::new(false, |&current: &bool, next: &mut bool, _| {
+            *next = !current;
+        });
+
+        assert_eq!(automaton.run_for(5), 5);
+        assert!(*automaton.state());
+    }
+
+    #[test]
+    fn rotating_priority() {
+        let priority = RotatingPriority::new(['a', 'b', 'c']);
+
+        assert_eq!(priority.order(0).collect::<String>(), "abc");
+        assert_eq!(priority.order(4).collect::<String>(), "bca");
+    }
+}
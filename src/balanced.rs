@@ -0,0 +1,211 @@
+//! Numbers of any length written in a balanced base, where digits run from `-(N / 2)` to `N / 2`.
+
+use std::{
+    fmt,
+    iter::Sum,
+    ops::{Add, Neg},
+    str::FromStr,
+};
+
+/// A number in balanced base `N`, for odd `N` up to 5. Digits above 0 are written as usual, and
+/// `-1` and `-2` as `-` and `=`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BalancedBase<const N: u32> {
+    /// Least significant first, with no zeros at the most significant end
+    digits: Vec<i8>,
+}
+
+impl<const N: u32> BalancedBase<N> {
+    const HALF: i8 = {
+        assert!(
+            N % 2 == 1 && N <= 5,
+            "balanced bases need an odd base of at most 5"
+        );
+        (N / 2) as i8
+    };
+
+    fn trim(mut self) -> Self {
+        while self.digits.last() == Some(&0) {
+            self.digits.pop();
+        }
+        self
+    }
+
+    fn digit(symbol: char) -> Option<i8> {
+        let digit = match symbol {
+            '=' => -2,
+            '-' => -1,
+            '0'..='9' => symbol as i8 - b'0' as i8,
+            _ => return None,
+        };
+        (digit.abs() <= Self::HALF).then_some(digit)
+    }
+
+    fn symbol(digit: i8) -> char {
+        match digit {
+            -2 => '=',
+            -1 => '-',
+            _ => (b'0' + digit as u8) as char,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseBalancedError {
+    Empty,
+    InvalidDigit(char),
+}
+
+impl fmt::Display for ParseBalancedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseBalancedError::Empty => write!(f, "no digits"),
+            ParseBalancedError::InvalidDigit(symbol) => write!(f, "invalid digit {symbol:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseBalancedError {}
+
+impl<const N: u32> FromStr for BalancedBase<N> {
+    type Err = ParseBalancedError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ParseBalancedError::Empty);
+        }
+
+        let digits = s
+            .chars()
+            .rev()
+            .map(|symbol| Self::digit(symbol).ok_or(ParseBalancedError::InvalidDigit(symbol)))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { digits }.trim())
+    }
+}
+
+impl<const N: u32> fmt::Display for BalancedBase<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.digits.is_empty() {
+            return write!(f, "0");
+        }
+
+        let symbols: String = self.digits.iter().rev().map(|&d| Self::symbol(d)).collect();
+        write!(f, "{symbols}")
+    }
+}
+
+impl<const N: u32> Add for &BalancedBase<N> {
+    type Output = BalancedBase<N>;
+
+    fn add(self, rhs: Self) -> BalancedBase<N> {
+        let base = N as i8;
+        let length = self.digits.len().max(rhs.digits.len());
+        let digit = |number: &BalancedBase<N>, i| number.digits.get(i).copied().unwrap_or(0);
+
+        let mut digits = Vec::with_capacity(length + 1);
+        let mut carry = 0;
+        for i in 0..length {
+            // At most N away from 0, so a single carry brings it back in range
+            let mut sum = digit(self, i) + digit(rhs, i) + carry;
+            carry = 0;
+            if sum > BalancedBase::<N>::HALF {
+                sum -= base;
+                carry = 1;
+            } else if sum < -BalancedBase::<N>::HALF {
+                sum += base;
+                carry = -1;
+            }
+            digits.push(sum);
+        }
+        digits.push(carry);
+
+        BalancedBase { digits }.trim()
+    }
+}
+
+impl<const N: u32> Add for BalancedBase<N> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        &self + &rhs
+    }
+}
+
+impl<const N: u32> Neg for BalancedBase<N> {
+    type Output = Self;
+
+    fn neg(mut self) -> Self {
+        for digit in self.digits.iter_mut() {
+            *digit = -*digit;
+        }
+        self
+    }
+}
+
+impl<const N: u32> Sum for BalancedBase<N> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |total, number| total + number)
+    }
+}
+
+impl<'a, const N: u32> Sum<&'a BalancedBase<N>> for BalancedBase<N> {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |total, number| &total + number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Snafu = BalancedBase<5>;
+    type Ternary = BalancedBase<3>;
+
+    fn snafu(s: &str) -> Snafu {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn round_trips() {
+        for s in [
+            "1=-0-2", "12111", "2=0=", "21", "2=01", "111", "20012", "1=-1=", "0",
+        ] {
+            assert_eq!(snafu(s).to_string(), s);
+        }
+        assert_eq!(snafu("00012").to_string(), "12");
+        assert_eq!(snafu("0").to_string(), "0");
+
+        assert_eq!("".parse::<Snafu>(), Err(ParseBalancedError::Empty));
+        assert_eq!(
+            "13".parse::<Snafu>(),
+            Err(ParseBalancedError::InvalidDigit('3'))
+        );
+        assert_eq!(
+            "1=".parse::<Ternary>(),
+            Err(ParseBalancedError::InvalidDigit('='))
+        );
+    }
+
+    #[test]
+    fn arithmetic() {
+        // 1 + 2 = 3 = 1= and 4 = 1-
+        assert_eq!((snafu("1") + snafu("2")).to_string(), "1=");
+        assert_eq!(
+            [snafu("2"), snafu("2")].iter().sum::<Snafu>().to_string(),
+            "1-"
+        );
+        assert_eq!((snafu("1=-0-2") + -snafu("1=-0-2")).to_string(), "0");
+        assert_eq!((-snafu("2=")).to_string(), "=2");
+
+        // Longer than any machine integer, with a carry all the way along
+        let twos = snafu(&"2".repeat(60));
+        assert_eq!(
+            (twos + snafu("1")).to_string(),
+            format!("1{}", "=".repeat(60))
+        );
+
+        let one: Ternary = "1".parse().unwrap();
+        assert_eq!((one.clone() + one.clone() + one).to_string(), "10");
+    }
+}
@@ -0,0 +1,286 @@
+//! Fixed-size sets of small integers, stored a bit per possible element.
+
+use std::{
+    fmt,
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Sub, SubAssign},
+};
+
+/// A set of the integers below `64 * WORDS`
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BitSet<const WORDS: usize> {
+    words: [u64; WORDS],
+}
+
+pub type BitSet64 = BitSet<1>;
+pub type BitSet128 = BitSet<2>;
+
+impl<const WORDS: usize> Default for BitSet<WORDS> {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl<const WORDS: usize> BitSet<WORDS> {
+    pub const CAPACITY: usize = 64 * WORDS;
+    pub const EMPTY: Self = Self { words: [0; WORDS] };
+
+    pub fn new() -> Self {
+        Self::EMPTY
+    }
+
+    /// Every integer below `universe`
+    pub fn full(universe: usize) -> Self {
+        assert!(universe <= Self::CAPACITY, "{universe} doesn't fit");
+        let mut set = Self::EMPTY;
+        for (i, word) in set.words.iter_mut().enumerate() {
+            *word = match universe.saturating_sub(64 * i) {
+                0 => 0,
+                bits @ 1..=63 => (1 << bits) - 1,
+                _ => u64::MAX,
+            };
+        }
+        set
+    }
+
+    /// Returns whether `element` wasn't already in the set
+    pub fn insert(&mut self, element: usize) -> bool {
+        let (word, bit) = Self::locate(element);
+        let added = self.words[word] & bit == 0;
+        self.words[word] |= bit;
+        added
+    }
+
+    /// Returns whether `element` was in the set
+    pub fn remove(&mut self, element: usize) -> bool {
+        let (word, bit) = Self::locate(element);
+        let removed = self.words[word] & bit != 0;
+        self.words[word] &= !bit;
+        removed
+    }
+
+    pub fn contains(&self, element: usize) -> bool {
+        element < Self::CAPACITY && self.words[element / 64] & (1 << (element % 64)) != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    /// The smallest element
+    pub fn first(&self) -> Option<usize> {
+        self.iter().next()
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        *self | *other
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        *self & *other
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        *self - *other
+    }
+
+    /// Every integer below `universe` that isn't in the set
+    pub fn complement(&self, universe: usize) -> Self {
+        Self::full(universe) - *self
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        (*self - *other).is_empty()
+    }
+
+    /// The elements in increasing order
+    pub fn iter(&self) -> Iter<WORDS> {
+        Iter {
+            words: self.words,
+            word: 0,
+        }
+    }
+
+    /// Every subset of this set, starting with the empty set and ending with the set itself
+    pub fn subsets(&self) -> impl Iterator<Item = Self> {
+        let elements: Vec<usize> = self.iter().collect();
+        assert!(elements.len() < 64, "too many subsets to enumerate");
+
+        (0..1u64 << elements.len()).map(move |mask| {
+            elements
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| mask & (1 << i) != 0)
+                .map(|(_, &element)| element)
+                .collect()
+        })
+    }
+
+    fn locate(element: usize) -> (usize, u64) {
+        assert!(element < Self::CAPACITY, "{element} doesn't fit");
+        (element / 64, 1 << (element % 64))
+    }
+
+    fn zip_with(self, other: Self, f: impl Fn(u64, u64) -> u64) -> Self {
+        let mut words = self.words;
+        for (word, other) in words.iter_mut().zip(other.words) {
+            *word = f(*word, other);
+        }
+        Self { words }
+    }
+}
+
+impl<const WORDS: usize> fmt::Debug for BitSet<WORDS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<const WORDS: usize> BitOr for BitSet<WORDS> {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        self.zip_with(rhs, |a, b| a | b)
+    }
+}
+
+impl<const WORDS: usize> BitAnd for BitSet<WORDS> {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        self.zip_with(rhs, |a, b| a & b)
+    }
+}
+
+/// Set difference
+impl<const WORDS: usize> Sub for BitSet<WORDS> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self.zip_with(rhs, |a, b| a & !b)
+    }
+}
+
+impl<const WORDS: usize> BitOrAssign for BitSet<WORDS> {
+    fn bitor_assign(&mut self, rhs: Self) {
+        *self = *self | rhs;
+    }
+}
+
+impl<const WORDS: usize> BitAndAssign for BitSet<WORDS> {
+    fn bitand_assign(&mut self, rhs: Self) {
+        *self = *self & rhs;
+    }
+}
+
+impl<const WORDS: usize> SubAssign for BitSet<WORDS> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<const WORDS: usize> FromIterator<usize> for BitSet<WORDS> {
+    fn from_iter<I: IntoIterator<Item = usize>>(elements: I) -> Self {
+        let mut set = Self::EMPTY;
+        for element in elements {
+            set.insert(element);
+        }
+        set
+    }
+}
+
+impl<const WORDS: usize> IntoIterator for BitSet<WORDS> {
+    type Item = usize;
+    type IntoIter = Iter<WORDS>;
+
+    fn into_iter(self) -> Iter<WORDS> {
+        self.iter()
+    }
+}
+
+impl<const WORDS: usize> IntoIterator for &BitSet<WORDS> {
+    type Item = usize;
+    type IntoIter = Iter<WORDS>;
+
+    fn into_iter(self) -> Iter<WORDS> {
+        self.iter()
+    }
+}
+
+/// See [`BitSet::iter`]
+#[derive(Debug, Clone)]
+pub struct Iter<const WORDS: usize> {
+    /// The elements not yet returned
+    words: [u64; WORDS],
+    /// Every word before this one is empty
+    word: usize,
+}
+
+impl<const WORDS: usize> Iterator for Iter<WORDS> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.word < WORDS {
+            let bits = &mut self.words[self.word];
+            if *bits != 0 {
+                let bit = bits.trailing_zeros() as usize;
+                *bits &= *bits - 1;
+                return Some(64 * self.word + bit);
+            }
+            self.word += 1;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    #[test]
+    fn set_operations() {
+        let mut set = BitSet128::new();
+        assert!(set.insert(3));
+        assert!(set.insert(70));
+        assert!(!set.insert(3));
+        assert!(set.contains(70));
+        assert!(!set.contains(4));
+        assert!(!set.contains(500));
+        assert_eq!(set.len(), 2);
+
+        let other: BitSet128 = [3, 5, 127].into_iter().collect();
+        assert_eq!(set.union(&other).iter().collect_vec(), [3, 5, 70, 127]);
+        assert_eq!(set.intersection(&other).iter().collect_vec(), [3]);
+        assert_eq!(set.difference(&other).iter().collect_vec(), [70]);
+        assert_eq!(set.complement(72).len(), 70);
+        assert_eq!(BitSet128::full(128).len(), 128);
+        assert_eq!(BitSet64::full(5).complement(5), BitSet64::EMPTY);
+        assert!(set.intersection(&other).is_subset(&set));
+
+        assert!(set.remove(70));
+        assert!(!set.remove(70));
+        assert_eq!(set.first(), Some(3));
+        assert_eq!(format!("{set:?}"), "{3}");
+    }
+
+    #[test]
+    fn enumerates_subsets() {
+        let set: BitSet64 = [1, 4, 9].into_iter().collect();
+        let subsets = set.subsets().collect_vec();
+
+        assert_eq!(subsets.len(), 8);
+        assert_eq!(subsets[0], BitSet64::EMPTY);
+        assert_eq!(subsets[7], set);
+        assert!(subsets.iter().all(|subset| subset.is_subset(&set)));
+        assert_eq!(subsets.iter().unique().count(), 8);
+        assert_eq!(BitSet64::EMPTY.subsets().count(), 1);
+    }
+}
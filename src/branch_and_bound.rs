@@ -0,0 +1,177 @@
+//! Depth-first branch and bound, for maximising over search trees too big to explore fully.
+
+use crate::memo::{Memo, Store};
+
+/// A maximisation problem over a tree of states
+pub trait Problem {
+    type State: Clone;
+
+    /// The states one decision away from `state`. They're explored in order, so putting the most
+    /// promising first lets the bound prune more.
+    fn successors(&self, state: &Self::State) -> Vec<Self::State>;
+
+    /// What the search gets if it stops at `state`
+    fn value(&self, state: &Self::State) -> usize;
+
+    /// At least as much as the value of any state reachable from `state`
+    fn bound(&self, state: &Self::State) -> usize;
+}
+
+/// The best state found, and how much work it took
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome<S> {
+    pub value: usize,
+    pub state: S,
+    pub expanded: usize,
+    pub pruned: usize,
+}
+
+/// The best value reachable from `initial`, skipping any state whose bound shows it can't beat the
+/// best found so far
+pub fn maximise<P: Problem>(problem: &P, initial: P::State) -> Outcome<P::State> {
+    search(problem, initial, |_| false)
+}
+
+/// Like [`maximise`], but also skips states dominated by one already expanded: one with the same
+/// `key` and at least as high a value. The key has to capture everything that decides what can
+/// still be gained from a state. The memo can keep values in a narrower type than `usize`, so
+/// dense tables stay small. Values too big for it just aren't remembered.
+pub fn maximise_with_memo<P, S>(
+    problem: &P,
+    initial: P::State,
+    memo: &mut Memo<S>,
+    mut key: impl FnMut(&P::State) -> S::Key,
+) -> Outcome<P::State>
+where
+    P: Problem,
+    S: Store,
+    S::Value: Copy + Into<usize> + TryFrom<usize>,
+{
+    search(problem, initial, |state| {
+        let key = key(state);
+        let value = problem.value(state);
+        if memo.get(&key).is_some_and(|seen| seen.into() >= value) {
+            return true;
+        }
+
+        if let Ok(value) = S::Value::try_from(value) {
+            memo.insert(key, value);
+        }
+        false
+    })
+}
+
+fn search<P: Problem>(
+    problem: &P,
+    initial: P::State,
+    mut dominated: impl FnMut(&P::State) -> bool,
+) -> Outcome<P::State> {
+    let mut outcome = Outcome {
+        value: problem.value(&initial),
+        state: initial.clone(),
+        expanded: 0,
+        pruned: 0,
+    };
+
+    let mut stack = vec![initial];
+    while let Some(state) = stack.pop() {
+        // Checked on the way out rather than the way in, since the best may have improved since
+        if problem.bound(&state) <= outcome.value || dominated(&state) {
+            outcome.pruned += 1;
+            continue;
+        }
+
+        outcome.expanded += 1;
+        let successors = problem.successors(&state);
+        for next in successors.iter() {
+            let value = problem.value(next);
+            if value > outcome.value {
+                outcome.value = value;
+                outcome.state = next.clone();
+            }
+        }
+        stack.extend(successors.into_iter().rev());
+    }
+
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memo::Hashed;
+
+    /// 0/1 knapsack, deciding on one item at a time
+    struct Knapsack {
+        /// `(weight, value)`, best value per unit of weight first
+        items: Vec<(usize, usize)>,
+        capacity: usize,
+    }
+
+    /// `(items decided, weight used, value packed)`
+    type Packing = (usize, usize, usize);
+
+    impl Problem for Knapsack {
+        type State = Packing;
+
+        fn successors(&self, &(decided, weight, value): &Packing) -> Vec<Packing> {
+            let Some(&(item_weight, item_value)) = self.items.get(decided) else {
+                return Vec::new();
+            };
+
+            let mut successors = vec![(decided + 1, weight, value)];
+            if weight + item_weight <= self.capacity {
+                successors.insert(0, (decided + 1, weight + item_weight, value + item_value));
+            }
+            successors
+        }
+
+        fn value(&self, &(_, _, value): &Packing) -> usize {
+            value
+        }
+
+        fn bound(&self, &(decided, _, value): &Packing) -> usize {
+            value
+                + self.items[decided..]
+                    .iter()
+                    .map(|&(_, value)| value)
+                    .sum::<usize>()
+        }
+    }
+
+    fn knapsack() -> Knapsack {
+        Knapsack {
+            items: vec![(5, 50), (4, 36), (3, 24), (2, 14), (2, 12), (4, 20)],
+            capacity: 10,
+        }
+    }
+
+    #[test]
+    fn finds_optimum() {
+        let outcome = maximise(&knapsack(), (0, 0, 0));
+
+        assert_eq!(outcome.value, 88);
+        assert_eq!(outcome.state.1, 10);
+        assert!(outcome.pruned > 0);
+        // Without pruning, all 127 states of the full tree would be expanded
+        assert!(outcome.expanded < 127);
+    }
+
+    #[test]
+    fn memo_prunes_dominated_states() {
+        let plain = maximise(&knapsack(), (0, 0, 0));
+
+        // Only the items decided and the weight used matter for what can still be packed
+        let mut memo: Memo<Hashed<_, u8>> = Memo::hashed();
+        let outcome = maximise_with_memo(
+            &knapsack(),
+            (0, 0, 0),
+            &mut memo,
+            |&(decided, weight, _)| (decided, weight),
+        );
+
+        assert_eq!(outcome.value, plain.value);
+        assert!(outcome.expanded < plain.expanded);
+        assert!(memo.stats().hits > 0);
+    }
+}
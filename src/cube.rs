@@ -0,0 +1,236 @@
+//! Folding a flat net of six squares into a cube, so that walking off the edge of one face carries
+//! on along the face it gets glued to.
+
+use crate::geom::{Direction, Point};
+use crate::grid::Grid;
+
+type Vec3 = [isize; 3];
+
+fn neg(v: Vec3) -> Vec3 {
+    v.map(|c| -c)
+}
+
+fn dot(a: Vec3, b: Vec3) -> isize {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+/// Where a face of the net ended up once folded: its outward normal, and the directions its local
+/// `x` and `y` axes point along
+#[derive(Debug, Clone, Copy)]
+struct Face {
+    tile: Point<usize>,
+    normal: Vec3,
+    right: Vec3,
+    down: Vec3,
+}
+
+impl Face {
+    /// The direction on the cube of an orthogonal direction on the face
+    fn along(&self, direction: Direction) -> Vec3 {
+        match direction {
+            Direction::Right => self.right,
+            Direction::Down => self.down,
+            Direction::Left => neg(self.right),
+            Direction::Up => neg(self.down),
+            _ => panic!("Only orthogonal directions can be followed on a cube"),
+        }
+    }
+
+    /// The orthogonal direction on the face pointing along `v`, which must lie in the face's plane
+    fn direction_of(&self, v: Vec3) -> Direction {
+        Direction::ORTHOGONAL
+            .into_iter()
+            .find(|&direction| self.along(direction) == v)
+            .expect("Vector doesn't lie in the face's plane")
+    }
+
+    /// The face you reach by folding the neighbouring tile of the net in `direction` over the edge
+    fn fold(&self, direction: Direction, tile: Point<usize>) -> Self {
+        let along = self.along(direction);
+        let (right, down) = match direction {
+            Direction::Right => (neg(self.normal), self.down),
+            Direction::Left => (self.normal, self.down),
+            Direction::Down => (self.right, neg(self.normal)),
+            Direction::Up => (self.right, self.normal),
+            _ => unreachable!(),
+        };
+
+        Self {
+            tile,
+            normal: along,
+            right,
+            down,
+        }
+    }
+}
+
+/// A cube net laid out on a grid, with its faces identified and their edges stitched together
+#[derive(Debug, Clone)]
+pub struct CubeNet {
+    size: usize,
+    faces: Vec<Face>,
+    /// The face at each tile of the net, if there is one
+    tiles: Grid<Option<usize>>,
+}
+
+impl CubeNet {
+    /// Fold the squares of `grid` for which `on_net` holds. The face size is worked out from the
+    /// number of squares, and the net can be any of the 11 that fold into a cube.
+    pub fn new<T>(grid: &Grid<T>, mut on_net: impl FnMut(&T) -> bool) -> Self {
+        let area = grid.count_where(&mut on_net);
+        let size = (1..).find(|size| 6 * size * size >= area).unwrap();
+        assert_eq!(6 * size * size, area, "Net doesn't have 6 square faces");
+
+        let mut tiles = grid![None; grid.width().div_ceil(size), grid.height().div_ceil(size)];
+        let mut faces = Vec::with_capacity(6);
+
+        let first = tiles
+            .positions()
+            .find(|&(x, y)| grid.get((x * size, y * size)).is_some_and(&mut on_net))
+            .expect("Net is empty");
+        faces.push(Face {
+            tile: first.into(),
+            normal: [0, 0, 1],
+            right: [1, 0, 0],
+            down: [0, 1, 0],
+        });
+        tiles[first] = Some(0);
+
+        let mut next = 0;
+        while let Some(&face) = faces.get(next) {
+            for direction in Direction::ORTHOGONAL {
+                let Some(tile) = face.tile.checked_add_signed(direction.vector()) else {
+                    continue;
+                };
+                if tiles.get(tile) != Some(&None)
+                    || !grid
+                        .get((tile.x * size, tile.y * size))
+                        .is_some_and(&mut on_net)
+                {
+                    continue;
+                }
+
+                tiles[tile] = Some(faces.len());
+                faces.push(face.fold(direction, tile));
+            }
+            next += 1;
+        }
+
+        assert_eq!(faces.len(), 6, "Net doesn't have 6 connected faces");
+        assert!(
+            faces
+                .iter()
+                .enumerate()
+                .all(|(i, a)| faces[..i].iter().all(|b| a.normal != b.normal)),
+            "Net doesn't fold into a cube"
+        );
+
+        Self { size, faces, tiles }
+    }
+
+    /// The index of the face `position` is on, if any
+    pub fn face_at(&self, position: Point<usize>) -> Option<usize> {
+        let tile = (position.x / self.size, position.y / self.size);
+        self.tiles.get(tile).copied().flatten()
+    }
+
+    /// Take one step from `position` on the net, returning where you end up and which way you're
+    /// facing once there. Crossing onto a face that isn't adjacent on the net can turn you around.
+    pub fn step(&self, position: Point<usize>, direction: Direction) -> (Point<usize>, Direction) {
+        let face = &self.faces[self.face_at(position).expect("Position isn't on the net")];
+        let origin = face.tile * self.size;
+        let local = (position - origin).to_signed() + direction;
+
+        let size = self.size as isize;
+        if (0..size).contains(&local.x) && (0..size).contains(&local.y) {
+            return (origin + local.to_unsigned().unwrap(), direction);
+        }
+
+        // Work on the cube itself, with coordinates doubled so that square centres are integers:
+        // the cube spans -size..=size on every axis.
+        let on_cube = |v: isize| 2 * v + 1 - size;
+        let leaving = (position - origin).to_signed();
+        let centre: Vec3 = std::array::from_fn(|axis| {
+            face.normal[axis] * size
+                + face.right[axis] * on_cube(leaving.x)
+                + face.down[axis] * on_cube(leaving.y)
+        });
+
+        let along = face.along(direction);
+        let next_face = self
+            .faces
+            .iter()
+            .find(|next_face| next_face.normal == along)
+            .unwrap();
+        let next_centre: Vec3 =
+            std::array::from_fn(|axis| centre[axis] + along[axis] - face.normal[axis]);
+
+        let next_local = Point::new(
+            (dot(next_centre, next_face.right) + size - 1) / 2,
+            (dot(next_centre, next_face.down) + size - 1) / 2,
+        );
+
+        (
+            next_face.tile * self.size + next_local.to_unsigned().unwrap(),
+            next_face.direction_of(neg(face.normal)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    /// The net from the puzzle's example, with faces of size 4
+    fn example() -> CubeNet {
+        let tiles = ["..#.", "###.", "..##"];
+        let grid: Grid<bool> = (0..12)
+            .map(|y| (0..16).map(move |x| tiles[y / 4].as_bytes()[x / 4] == b'#'))
+            .collect();
+
+        CubeNet::new(&grid, |&on_net| on_net)
+    }
+
+    #[test]
+    fn example_edges() {
+        let net = example();
+
+        // A to B and C to D from the puzzle description
+        assert_eq!(
+            net.step(Point::new(11, 5), Direction::Right),
+            (Point::new(14, 8), Direction::Down)
+        );
+        assert_eq!(
+            net.step(Point::new(10, 11), Direction::Down),
+            (Point::new(1, 7), Direction::Up)
+        );
+        // Staying on the net
+        assert_eq!(
+            net.step(Point::new(3, 5), Direction::Right),
+            (Point::new(4, 5), Direction::Right)
+        );
+    }
+
+    #[test]
+    fn steps_can_be_retraced() {
+        let net = example();
+
+        for (x, y) in (0..16).cartesian_product(0..12) {
+            let position = Point::new(x, y);
+            if net.face_at(position).is_none() {
+                continue;
+            }
+
+            for direction in Direction::ORTHOGONAL {
+                let (next, facing) = net.step(position, direction);
+                assert_eq!(
+                    net.step(next, facing.reverse()),
+                    (position, direction.reverse()),
+                    "{position} {direction:?}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! Spotting when a simulation starts repeating itself, so that it can be extrapolated far past
+//! the point it would be practical to run it to.
+
+use std::{collections::hash_map::Entry, hash::Hash};
+
+use rustc_hash::FxHashMap as HashMap;
+
+/// A measured quantity that repeats with a fixed period after some point, growing by the same
+/// amount every period
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    /// The first step that's part of the cycle
+    pub start: usize,
+    pub period: usize,
+    /// How much the quantity grows over one period
+    pub delta: usize,
+    /// The quantity at every step up to the end of the first period
+    values: Vec<usize>,
+}
+
+impl Cycle {
+    /// The quantity after `step` steps
+    pub fn extrapolate(&self, step: usize) -> usize {
+        if step < self.values.len() {
+            return self.values[step];
+        }
+
+        let (periods, offset) = (
+            (step - self.start) / self.period,
+            (step - self.start) % self.period,
+        );
+        self.values[self.start + offset] + periods * self.delta
+    }
+}
+
+/// Step `state` until its `fingerprint` comes up a second time, measuring the quantity of
+/// interest after every step. The fingerprint has to capture everything that affects how the
+/// state evolves, and the quantity must never shrink. Never returns if the state doesn't repeat.
+pub fn find_cycle<S, K>(
+    mut state: S,
+    mut step: impl FnMut(&mut S),
+    mut fingerprint: impl FnMut(&S) -> K,
+    mut measure: impl FnMut(&S) -> usize,
+) -> Cycle
+where
+    K: Hash + Eq,
+{
+    let mut seen = HashMap::default();
+    let mut values = Vec::new();
+
+    loop {
+        let steps = values.len();
+        values.push(measure(&state));
+
+        match seen.entry(fingerprint(&state)) {
+            Entry::Occupied(entry) => {
+                let start = *entry.get();
+                return Cycle {
+                    start,
+                    period: steps - start,
+                    delta: values[steps] - values[start],
+                    values,
+                };
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(steps);
+            }
+        }
+
+        step(&mut state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extrapolates() {
+        // Counts 0, 1, 2 then repeats 3, 4, 5, 6, 7 forever, gaining 25 every time round
+        let cycle = find_cycle(
+            (0, 0),
+            |(position, total)| {
+                *position = if *position == 7 { 3 } else { *position + 1 };
+                *total += *position;
+            },
+            |&(position, _)| position,
+            |&(_, total)| total,
+        );
+
+        assert_eq!(cycle.start, 3);
+        assert_eq!(cycle.period, 5);
+        assert_eq!(cycle.delta, 3 + 4 + 5 + 6 + 7);
+
+        let mut brute_force = (0, 0);
+        for step in 0..100 {
+            assert_eq!(cycle.extrapolate(step), brute_force.1, "step {step}");
+            brute_force.0 = if brute_force.0 == 7 {
+                3
+            } else {
+                brute_force.0 + 1
+            };
+            brute_force.1 += brute_force.0;
+        }
+    }
+}
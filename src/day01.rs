@@ -1,35 +1,56 @@
+use std::io::{self, BufRead};
+
 #[allow(unused)]
 use crate::prelude::*;
+use crate::{
+    input::{self, LineByLine},
+    parse::{self, ParseError},
+};
 
-fn parse_int_faster(ascii_bytes: &[u8]) -> usize {
-    let mut total: usize = 0;
-    for byte in ascii_bytes {
-        total *= 10;
-        total += (byte - b'0') as usize;
-    }
-    total
+#[derive(Default)]
+struct Elves {
+    /// The three elves carrying the most so far, and a spare slot at the front. Invariant: always
+    /// sorted in ascending order.
+    heapish: [usize; 4],
+    /// Calories carried by the elf being counted
+    elf: usize,
 }
 
-pub fn run(input: &str) -> (Solution, Solution) {
-    // Invariant: always sorted in ascending order
-    let mut heapish = [0usize; 4];
+impl Elves {
+    fn next_elf(&mut self) {
+        self.heapish[0] = self.elf;
+        self.heapish.sort();
+        self.elf = 0;
+    }
+}
 
-    let mut elf = 0usize;
-    let input = input.as_bytes();
-    for line in input.split(|&byte| byte == b'\n') {
+impl LineByLine for Elves {
+    fn line<'a>(&mut self, line: &'a [u8]) -> Result<(), ParseError<'a>> {
         if line.is_empty() {
-            heapish[0] = elf;
-            heapish.sort();
-            elf = 0;
-            continue;
+            self.next_elf();
+        } else {
+            self.elf += parse::int_exact::<usize>(line)?;
         }
-        elf += parse_int_faster(line)
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<(Solution, Solution), ParseError<'static>> {
+        // The last elf doesn't need a blank line after it
+        self.next_elf();
+
+        Ok((
+            self.heapish[3].into(),
+            self.heapish.into_iter().skip(1).sum::<usize>().into(),
+        ))
     }
+}
+
+pub fn run(input: &str) -> (Solution, Solution) {
+    input::solve::<Elves>(input)
+}
 
-    (
-        heapish[3].into(),
-        heapish.into_iter().skip(1).sum::<usize>().into(),
-    )
+pub fn run_streaming(input: &mut dyn BufRead) -> io::Result<(Solution, Solution)> {
+    input::solve_streaming::<Elves>(input)
 }
 
 #[cfg(test)]
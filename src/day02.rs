@@ -1,3 +1,7 @@
+use std::io::{self, BufRead};
+
+use crate::input::{self, LineByLine};
+use crate::parse::{ParseError, ParseErrorKind};
 #[allow(unused)]
 use crate::prelude::*;
 
@@ -70,42 +74,82 @@ const fn part2_score(round: &[u8]) -> usize {
     )
 }
 
-pub fn run(input: &str) -> (Solution, Solution) {
-    const LOOKUP_TABLE: [(usize, usize); 9] = {
-        let cases = [
-            b"A X", b"A Y", b"A Z", b"B X", b"B Y", b"B Z", b"C X", b"C Y", b"C Z",
-        ];
+/// Both parts' scores for every possible round, by [`index`]
+const LOOKUP_TABLE: [(usize, usize); 9] = {
+    let cases = [
+        b"A X", b"A Y", b"A Z", b"B X", b"B Y", b"B Z", b"C X", b"C Y", b"C Z",
+    ];
 
-        let mut scores = [(0, 0); 9];
+    let mut scores = [(0, 0); 9];
 
-        let mut i = 0;
+    let mut i = 0;
 
-        loop {
-            if i >= cases.len() {
-                break;
-            };
-            assert!(i == index(cases[i]));
+    loop {
+        if i >= cases.len() {
+            break;
+        };
+        assert!(i == index(cases[i]));
 
-            scores[i].0 = part1_score(cases[i]);
-            scores[i].1 = part2_score(cases[i]);
+        scores[i].0 = part1_score(cases[i]);
+        scores[i].1 = part2_score(cases[i]);
 
-            i += 1;
-        }
+        i += 1;
+    }
+
+    scores
+};
 
-        scores
-    };
+/// The [`index`] of a round like `A Y`
+fn parse_round(line: &[u8]) -> Result<usize, ParseError<'_>> {
+    if !matches!(line.first(), Some(b'A'..=b'C')) {
+        return Err(ParseError::new(
+            line,
+            ParseErrorKind::Invalid("opponent's play"),
+        ));
+    }
+    if line.get(1) != Some(&b' ') {
+        return Err(ParseError::new(&line[1..], ParseErrorKind::Expected(b' ')));
+    }
+    if !matches!(line.get(2), Some(b'X'..=b'Z')) {
+        return Err(ParseError::new(
+            &line[2..],
+            ParseErrorKind::Invalid("response"),
+        ));
+    }
+    if line.len() > 3 {
+        return Err(ParseError::new(&line[3..], ParseErrorKind::TrailingInput));
+    }
 
-    let mut part1 = 0;
-    let mut part2 = 0;
+    Ok(index(line))
+}
 
-    let input = input.as_bytes();
-    for line in input.chunks(4) {
-        let (result1, result2) = LOOKUP_TABLE[index(line)];
-        part1 += result1;
-        part2 += result2;
+#[derive(Default)]
+struct Scores {
+    part1: usize,
+    part2: usize,
+}
+
+impl LineByLine for Scores {
+    fn line<'a>(&mut self, line: &'a [u8]) -> Result<(), ParseError<'a>> {
+        if !line.is_empty() {
+            let (result1, result2) = LOOKUP_TABLE[parse_round(line)?];
+            self.part1 += result1;
+            self.part2 += result2;
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(Solution, Solution), ParseError<'static>> {
+        Ok((self.part1.into(), self.part2.into()))
     }
+}
+
+pub fn run(input: &str) -> (Solution, Solution) {
+    input::solve::<Scores>(input)
+}
 
-    (part1.into(), part2.into())
+pub fn run_streaming(input: &mut dyn BufRead) -> io::Result<(Solution, Solution)> {
+    input::solve_streaming::<Scores>(input)
 }
 
 // #[derive(Clone, Copy)]
@@ -1,45 +1,104 @@
+use std::io::{self, BufRead};
+
+use crate::bitset::BitSet64;
+use crate::input::{self, LineByLine};
+use crate::parse::{ParseError, ParseErrorKind};
 #[allow(unused)]
 use crate::prelude::*;
 
-fn byte_bit(byte: u8) -> u64 {
-    1 << (match byte {
-        b'a'..=b'z' => byte - b'a',
-        b'A'..=b'Z' => 26 + byte - b'A',
-        _ => unreachable!(),
-    }) as usize
+/// One less than the item's priority
+fn item_index(byte: u8) -> Option<usize> {
+    match byte {
+        b'a'..=b'z' => Some((byte - b'a') as usize),
+        b'A'..=b'Z' => Some((26 + byte - b'A') as usize),
+        _ => None,
+    }
 }
 
-fn process_line(line: &[u8]) -> (u32, u64) {
-    let (left, right) = line.split_at(line.len() / 2);
+fn items(bytes: &[u8]) -> Result<BitSet64, ParseError<'_>> {
+    bytes
+        .iter()
+        .enumerate()
+        .map(|(i, &byte)| {
+            item_index(byte).ok_or(ParseError::new(
+                &bytes[i..],
+                ParseErrorKind::Invalid("item"),
+            ))
+        })
+        .collect()
+}
 
-    let left_set = left.iter().fold(0u64, |set, &byte| set | byte_bit(byte));
-    let right_set = right.iter().fold(0u64, |set, &byte| set | byte_bit(byte));
+/// The priority of the item in both compartments, and every item in the rucksack
+fn process_line(line: &[u8]) -> Result<(usize, BitSet64), ParseError<'_>> {
+    if !line.len().is_multiple_of(2) {
+        return Err(ParseError::new(
+            line,
+            ParseErrorKind::Invalid("rucksack with an odd number of items"),
+        ));
+    }
+
+    let (left, right) = line.split_at(line.len() / 2);
+    let (left_set, right_set) = (items(left)?, items(right)?);
+    let shared = (left_set & right_set).first().ok_or(ParseError::new(
+        line,
+        ParseErrorKind::Invalid("rucksack with no item in both compartments"),
+    ))?;
 
-    (
-        (left_set & right_set).trailing_zeros() + 1,
-        left_set | right_set,
-    )
+    Ok((shared + 1, left_set | right_set))
 }
 
-pub fn run(input: &str) -> (Solution, Solution) {
-    let mut part1_score = 0;
-    let mut part2_score = 0;
+#[derive(Default)]
+struct Rucksacks {
+    part1_score: usize,
+    part2_score: usize,
+    /// The items every rucksack so far in this group of three has in common
+    group: Option<BitSet64>,
+    group_size: usize,
+}
 
-    let mut lines = input
-        .as_bytes()
-        .split(|&byte| byte == b'\n')
-        .filter(|line| !line.is_empty());
+impl LineByLine for Rucksacks {
+    fn line<'a>(&mut self, line: &'a [u8]) -> Result<(), ParseError<'a>> {
+        if line.is_empty() {
+            return Ok(());
+        }
+
+        let (score, set) = process_line(line)?;
+        self.part1_score += score;
+
+        let group = self.group.map_or(set, |group| group & set);
+        self.group_size += 1;
+        if self.group_size == 3 {
+            let badge = group.first().ok_or(ParseError::new(
+                line,
+                ParseErrorKind::Invalid("group with no item in common"),
+            ))?;
+            self.part2_score += badge + 1;
+            self.group = None;
+            self.group_size = 0;
+        } else {
+            self.group = Some(group);
+        }
+        Ok(())
+    }
 
-    while let Some(line) = lines.next() {
-        let (score_1, set_1) = process_line(line);
-        let (score_2, set_2) = process_line(lines.next().unwrap());
-        let (score_3, set_3) = process_line(lines.next().unwrap());
+    fn finish(self) -> Result<(Solution, Solution), ParseError<'static>> {
+        if self.group_size != 0 {
+            return Err(ParseError::new(
+                &[],
+                ParseErrorKind::Incomplete("group of three rucksacks"),
+            ));
+        }
 
-        part1_score += score_1 + score_2 + score_3;
-        part2_score += (set_1 & set_2 & set_3).trailing_zeros() + 1;
+        Ok((self.part1_score.into(), self.part2_score.into()))
     }
+}
+
+pub fn run(input: &str) -> (Solution, Solution) {
+    input::solve::<Rucksacks>(input)
+}
 
-    ((part1_score as usize).into(), (part2_score as usize).into())
+pub fn run_streaming(input: &mut dyn BufRead) -> io::Result<(Solution, Solution)> {
+    input::solve_streaming::<Rucksacks>(input)
 }
 
 // Original solution
@@ -1,37 +1,53 @@
+use std::io::{self, BufRead};
+
+use crate::input::{self, LineByLine};
+use crate::parse::{self, ParseError};
 #[allow(unused)]
 use crate::prelude::*;
 
-fn parse_int(mut bytes: &[u8], delimiter: u8) -> (&[u8], u8) {
-    let mut num = 0;
-    while bytes[0] != delimiter {
-        num *= 10;
-        num += bytes[0] - b'0';
-        bytes = &bytes[1..];
-    }
-    (&bytes[1..], num)
+/// Two ranges like `2-4,6-8`
+fn parse_pair(line: &[u8]) -> Result<[u8; 4], ParseError<'_>> {
+    let (line, s1) = parse::int_until(line, b'-')?;
+    let (line, e1) = parse::int_until(line, b',')?;
+    let (line, s2) = parse::int_until(line, b'-')?;
+    let e2 = parse::int_exact(line)?;
+    Ok([s1, e1, s2, e2])
 }
 
-pub fn run(input: &str) -> (Solution, Solution) {
-    let mut bytes = input.as_bytes();
+/// How many pairs have one range inside the other, and how many overlap at all
+#[derive(Default)]
+struct Overlaps {
+    contained: usize,
+    overlapping: usize,
+}
 
-    let mut result1 = 0;
-    let mut result2 = 0;
+impl LineByLine for Overlaps {
+    fn line<'a>(&mut self, line: &'a [u8]) -> Result<(), ParseError<'a>> {
+        if line.is_empty() {
+            return Ok(());
+        }
 
-    while !bytes.is_empty() {
-        let (s1, e1, s2, e2);
-        (bytes, s1) = parse_int(bytes, b'-');
-        (bytes, e1) = parse_int(bytes, b',');
-        (bytes, s2) = parse_int(bytes, b'-');
-        (bytes, e2) = parse_int(bytes, b'\n');
+        let [s1, e1, s2, e2] = parse_pair(line)?;
 
         let contained = (s1 <= s2 && e2 <= e1) || (s2 <= s1 && e1 <= e2);
-        result1 += contained as usize;
+        self.contained += contained as usize;
 
         let overlapping = (s1 <= s2 && s2 <= e1) || (s2 <= s1 && s1 <= e2);
-        result2 += overlapping as usize;
+        self.overlapping += overlapping as usize;
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(Solution, Solution), ParseError<'static>> {
+        Ok((self.contained.into(), self.overlapping.into()))
     }
+}
+
+pub fn run(input: &str) -> (Solution, Solution) {
+    input::solve::<Overlaps>(input)
+}
 
-    (result1.into(), result2.into())
+pub fn run_streaming(input: &mut dyn BufRead) -> io::Result<(Solution, Solution)> {
+    input::solve_streaming::<Overlaps>(input)
 }
 
 #[cfg(test)]
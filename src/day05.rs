@@ -9,6 +9,8 @@ use nom::{
     IResult,
 };
 
+use crate::input;
+use crate::parse::{self, ParseError, ParseErrorKind};
 #[allow(unused)]
 use crate::prelude::*;
 
@@ -29,7 +31,10 @@ fn parse_stack_entry(input: &[u8]) -> IResult<&[u8], Option<u8>> {
 fn parse_stack_row<'a>(input: &'a [u8], stacks: &mut Vec<VecDeque<u8>>) -> IResult<&'a [u8], ()> {
     let (remaining, row) = separated_list1(tag(" "), parse_stack_entry)(input)?;
 
-    stacks.resize(row.len(), VecDeque::new());
+    // Rows can stop early if their last stacks are empty
+    if stacks.len() < row.len() {
+        stacks.resize(row.len(), VecDeque::new());
+    }
 
     for (i, item) in row.into_iter().enumerate() {
         if let Some(item) = item {
@@ -40,40 +45,37 @@ fn parse_stack_row<'a>(input: &'a [u8], stacks: &mut Vec<VecDeque<u8>>) -> IResu
     Ok((remaining, ()))
 }
 
-fn parse_int(mut bytes: &[u8], delimiter: u8) -> (&[u8], usize) {
-    let mut num = 0;
-    while bytes[0] != delimiter {
-        num *= 10;
-        num += (bytes[0] - b'0') as usize;
-        bytes = &bytes[1..];
-    }
-    (&bytes[1..], num)
+/// `move 1 from 2 to 3`, with the stacks counted from 0
+fn parse_instruction(line: &[u8]) -> Result<(usize, usize, usize), ParseError<'_>> {
+    let (line, count) = parse::int_until(parse::tag(line, "move ")?, b' ')?;
+    let line = parse::tag(line, "from ")?;
+    let (rest, from) = parse::int_until(line, b' ')?;
+    let from = stack_index(line, from)?;
+    let line = parse::tag(rest, "to ")?;
+    let to = stack_index(line, parse::int_exact(line)?)?;
+    Ok((count, from, to))
 }
 
-fn parse_instruction(mut input: &[u8]) -> (&[u8], usize, usize, usize) {
-    let (count, from, to);
-    (input, count) = parse_int(&input[5..], b' ');
-    (input, from) = parse_int(&input[5..], b' ');
-    (input, to) = parse_int(&input[3..], b'\n');
-    (input, count, from - 1, to - 1)
+/// The stack `number`ed from 1 in `at`, counted from 0
+fn stack_index(at: &[u8], number: usize) -> Result<usize, ParseError<'_>> {
+    number
+        .checked_sub(1)
+        .ok_or(ParseError::new(at, ParseErrorKind::Invalid("stack number")))
 }
 
 pub fn run(input: &str) -> (Solution, Solution) {
     let mut stacks = Vec::new();
 
-    let (stack, instructions) = input.split_once("\n\n").unwrap();
-
-    for line in stack.lines() {
-        let _ = parse_stack_row(line.as_bytes(), &mut stacks);
+    // The stacks, then a blank line, then the instructions
+    let mut lines = input::lines(input.as_bytes());
+    for line in lines.by_ref().take_while(|line| !line.is_empty()) {
+        let _ = parse_stack_row(line, &mut stacks);
     }
 
-    let mut instructions_bytes = instructions.as_bytes();
-    let mut instructions = Vec::new();
-    while !instructions_bytes.is_empty() {
-        let (count, from, to);
-        (instructions_bytes, count, from, to) = parse_instruction(instructions_bytes);
-        instructions.push((count, from, to));
-    }
+    let instructions = lines
+        .filter(|line| !line.is_empty())
+        .map(|line| parse_instruction(line).unwrap_or_else(|error| error.report(input)))
+        .collect_vec();
 
     let result1: String = {
         let mut stacks = stacks.clone();
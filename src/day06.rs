@@ -1,25 +1,47 @@
+use std::io::{self, BufRead};
+
 #[allow(unused)]
 use crate::prelude::*;
 
-fn solve(input: &str, n: usize) -> usize {
-    let mut start = 0;
-    let mut seen_at = [None; 256];
-
-    let input = input.as_bytes();
-    for (i, &byte) in input.iter().enumerate() {
-        if let Some(index) = seen_at[byte as usize] {
-            if index >= start {
-                start = index + 1;
-            }
+/// Finds where the first `length` bytes in a row that are all different end, a byte at a time
+struct Marker {
+    length: usize,
+    /// Where the current run of different bytes starts
+    start: usize,
+    /// Bytes seen so far
+    position: usize,
+    seen_at: [Option<usize>; 256],
+}
+
+impl Marker {
+    fn new(length: usize) -> Self {
+        Self {
+            length,
+            start: 0,
+            position: 0,
+            seen_at: [None; 256],
         }
-        seen_at[byte as usize] = Some(i);
+    }
 
-        if i - start + 1 == n {
-            return i + 1;
+    /// The number of bytes up to the end of the marker, if `byte` finishes it
+    fn push(&mut self, byte: u8) -> Option<usize> {
+        let i = self.position;
+        self.position += 1;
+
+        if let Some(index) = self.seen_at[byte as usize] {
+            if index >= self.start {
+                self.start = index + 1;
+            }
         }
+        self.seen_at[byte as usize] = Some(i);
+
+        (i - self.start + 1 == self.length).then_some(i + 1)
     }
+}
 
-    unreachable!()
+fn solve(input: &str, n: usize) -> usize {
+    let mut marker = Marker::new(n);
+    input.bytes().find_map(|byte| marker.push(byte)).unwrap()
 }
 
 // Original solution was super nice but extremely slow
@@ -47,6 +69,36 @@ pub fn run(input: &str) -> (Solution, Solution) {
     (result1.into(), result2.into())
 }
 
+/// Like [`run`], but only reading as far into `input` as the markers
+pub fn run_streaming(input: &mut dyn BufRead) -> io::Result<(Solution, Solution)> {
+    let mut markers = [Marker::new(4), Marker::new(14)];
+    let mut found = [None; 2];
+
+    while found.contains(&None) {
+        let bytes = input.fill_buf()?;
+        if bytes.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the input ended before the markers",
+            ));
+        }
+
+        for &byte in bytes {
+            for (marker, found) in markers.iter_mut().zip(found.iter_mut()) {
+                if found.is_none() {
+                    *found = marker.push(byte);
+                }
+            }
+        }
+
+        let length = bytes.len();
+        input.consume(length);
+    }
+
+    let [result1, result2] = found.map(Option::unwrap);
+    Ok((result1.into(), result2.into()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -175,7 +175,7 @@ fn smallest_big_enough(directory: &Directory, target: usize) -> (usize, usize) {
     for entry in &directory.contents {
         match entry {
             FsEntry::Dir(subdir) => {
-                let (subsize, subbest) = smallest_big_enough(&subdir, target);
+                let (subsize, subbest) = smallest_big_enough(subdir, target);
                 my_size += subsize;
                 if subbest >= target {
                     best = best.min(subbest);
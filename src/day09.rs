@@ -1,14 +1,10 @@
 use crate::prelude::*;
 
-fn update_position(head_pos: (i32, i32), tail_pos: (i32, i32)) -> (i32, i32) {
-    let x_delta = head_pos.0 - tail_pos.0;
-    let y_delta = head_pos.1 - tail_pos.1;
-
-    match (x_delta, y_delta) {
-        (0 | 1 | -1, 0 | 1 | -1) => tail_pos,
-        (0, _) => (tail_pos.0, tail_pos.1 + y_delta.signum()),
-        (_, 0) => (tail_pos.0 + x_delta.signum(), tail_pos.1),
-        _ => (tail_pos.0 + x_delta.signum(), tail_pos.1 + y_delta.signum()),
+fn update_position(head_pos: Point<isize>, tail_pos: Point<isize>) -> Point<isize> {
+    if head_pos.chebyshev(tail_pos) <= 1 {
+        tail_pos
+    } else {
+        tail_pos + (head_pos - tail_pos).signum()
     }
 }
 
@@ -16,7 +12,7 @@ pub fn run(input: &str) -> (Solution, Solution) {
     const LAST: usize = 9;
     let mut seen_second = HashSet::default();
     let mut seen_last = HashSet::default();
-    let mut knots = [(0i32, 0i32); LAST + 1];
+    let mut knots = [Point::new(0isize, 0); LAST + 1];
 
     seen_second.insert(knots[1]);
     seen_last.insert(knots[LAST]);
@@ -25,17 +21,10 @@ pub fn run(input: &str) -> (Solution, Solution) {
         let (dir, count) = step.split_once(' ').unwrap();
         let count = count.parse::<usize>().unwrap();
 
-        let shift = match dir {
-            "U" => (0, 1),
-            "D" => (0, -1),
-            "L" => (-1, 0),
-            "R" => (1, 0),
-            _ => unreachable!(),
-        };
+        let shift = Direction::try_from(dir.chars().next().unwrap()).unwrap();
 
         for _ in 0..count {
-            knots[0].0 += shift.0;
-            knots[0].1 += shift.1;
+            knots[0] += shift;
 
             for i in 0..LAST {
                 knots[i + 1] = update_position(knots[i], knots[i + 1]);
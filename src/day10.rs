@@ -1,3 +1,7 @@
+use std::io::{self, BufRead};
+
+use crate::input::{self, LineByLine};
+use crate::parse::{self, ParseError, ParseErrorKind};
 #[allow(unused)]
 use crate::prelude::*;
 
@@ -6,42 +10,69 @@ enum Instruction {
     Add(i32),
 }
 
-pub fn run(input: &str) -> (Solution, Solution) {
-    let instructions = input.lines().map(|line| match line {
-        "noop" => Instruction::Noop,
-        _ => {
-            let (_, value) = line.split_once(' ').unwrap();
-            Instruction::Add(value.parse().unwrap())
+fn parse_instruction(line: &[u8]) -> Result<Instruction, ParseError<'_>> {
+    if line == b"noop" {
+        return Ok(Instruction::Noop);
+    }
+
+    let value = line.strip_prefix(b"addx ").ok_or(ParseError::new(
+        line,
+        ParseErrorKind::Invalid("instruction"),
+    ))?;
+    Ok(Instruction::Add(parse::int_exact(value)?))
+}
+
+struct Cpu {
+    total: i32,
+    register: i32,
+    cycle: usize,
+    row: [char; 40],
+    /// Every finished row of the CRT, each on a line of its own
+    screen: String,
+}
+
+impl Default for Cpu {
+    fn default() -> Self {
+        Self {
+            total: 0,
+            register: 1,
+            cycle: 0,
+            row: ['.'; 40],
+            screen: String::new(),
         }
-    });
+    }
+}
 
-    let mut total = 0;
-    let mut register = 1;
-    let mut cycle = 0;
-    let mut row = [' '; 40];
+impl LineByLine for Cpu {
+    fn line<'a>(&mut self, line: &'a [u8]) -> Result<(), ParseError<'a>> {
+        if line.is_empty() {
+            return Ok(());
+        }
 
-    for instruction in instructions {
+        let instruction = parse_instruction(line)?;
         let num_cycles = match instruction {
             Instruction::Noop => 1,
             Instruction::Add(_) => 2,
         };
 
         for _ in 0..num_cycles {
-            cycle += 1;
+            self.cycle += 1;
 
-            let mod_cycle: usize = (cycle - 1) % 40;
-            if (mod_cycle as i64).abs_diff(register as i64) <= 1 && (0i32..40).contains(&register) {
-                row[mod_cycle] = '#';
+            let mod_cycle: usize = (self.cycle - 1) % 40;
+            if (mod_cycle as i64).abs_diff(self.register as i64) <= 1
+                && (0i32..40).contains(&self.register)
+            {
+                self.row[mod_cycle] = '#';
             }
 
-            match cycle {
+            match self.cycle {
                 20 | 60 | 100 | 140 | 180 | 220 => {
-                    total += cycle as i32 * register;
+                    self.total += self.cycle as i32 * self.register;
                 }
                 40 | 80 | 120 | 160 | 200 | 240 => {
-                    let line = row.iter().copied().collect::<String>();
-                    println!("{line}");
-                    row.fill(' ');
+                    self.screen.push('\n');
+                    self.screen.extend(self.row);
+                    self.row.fill('.');
                 }
                 _ => (),
             }
@@ -49,14 +80,25 @@ pub fn run(input: &str) -> (Solution, Solution) {
 
         match instruction {
             Instruction::Noop => (),
-            Instruction::Add(val) => register += val,
+            Instruction::Add(val) => self.register += val,
         }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(Solution, Solution), ParseError<'static>> {
+        let result1 = self.total as usize;
+        let result2 = self.screen;
+
+        Ok((result1.into(), result2.into()))
     }
+}
 
-    let result1 = total as usize;
-    let result2 = 0;
+pub fn run(input: &str) -> (Solution, Solution) {
+    input::solve::<Cpu>(input)
+}
 
-    (result1.into(), result2.into())
+pub fn run_streaming(input: &mut dyn BufRead) -> io::Result<(Solution, Solution)> {
+    input::solve_streaming::<Cpu>(input)
 }
 
 #[cfg(test)]
@@ -13,6 +13,8 @@ use nom::{
     IResult,
 };
 
+use crate::math::lcm_of;
+use crate::parse;
 #[allow(unused)]
 use crate::prelude::*;
 
@@ -71,7 +73,7 @@ struct Test {
 
 impl Test {
     fn select_destination(self, worry_level: usize) -> usize {
-        if worry_level % self.divisible_by == 0 {
+        if worry_level.is_multiple_of(self.divisible_by) {
             self.if_true
         } else {
             self.if_false
@@ -87,10 +89,6 @@ struct Monkey {
     test: Test,
 }
 
-fn parse_usize(input: &str) -> IResult<&str, usize> {
-    map(nom::character::complete::u64, |n| n as usize)(input)
-}
-
 fn parse_items(input: &str) -> IResult<&str, Vec<usize>> {
     preceded(
         tag("  Starting items: "),
@@ -192,19 +190,15 @@ fn solve(monkeys: Vec<Monkey>, num_rounds: usize, reduce: impl Fn(usize) -> usiz
 }
 
 pub fn run(input: &str) -> (Solution, Solution) {
-    let (_, monkeys) = parse_input(input).unwrap();
+    let monkeys = parse::finish(parse_input(input)).unwrap_or_else(|error| error.report(input));
 
     let result1 = { solve(monkeys.clone(), 20, |worry_level| worry_level / 3) };
 
     let result2 = {
-        let product_of_divisors = monkeys
-            .iter()
-            .map(|monkey| monkey.test.divisible_by)
-            .product::<usize>();
-
-        solve(monkeys, 10_000, |worry_level| {
-            worry_level % product_of_divisors
-        })
+        // Every test only cares about the worry level modulo its own divisor
+        let common_multiple = lcm_of(monkeys.iter().map(|monkey| monkey.test.divisible_by));
+
+        solve(monkeys, 10_000, |worry_level| worry_level % common_multiple)
     };
 
     (result1.into(), result2.into())
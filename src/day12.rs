@@ -1,79 +1,52 @@
-use std::collections::VecDeque;
-
 #[allow(unused)]
 use crate::prelude::*;
+use crate::search::{Graph, Path};
 
-fn solve(
-    heightmap: &Grid<char>,
-    start: (usize, usize),
-    can_jump: impl Fn((usize, usize), (usize, usize)) -> bool,
-    done: impl Fn((usize, usize)) -> bool,
-) -> usize {
-    let mut visited: Grid<bool> = grid![false; heightmap.width(), heightmap.height()];
-    let mut horizon = VecDeque::from([(0, start)]);
+fn can_climb(from: char, to: char) -> bool {
+    (to as u8).saturating_sub(from as u8) <= 1
+}
 
-    while let Some((cost, vertex)) = horizon.pop_front() {
-        if done(vertex) {
-            return cost;
-        }
+struct Hill<'a>(&'a Grid<char>);
 
-        if visited[vertex] {
-            continue;
-        }
-        visited[vertex] = true;
+impl Graph for Hill<'_> {
+    type Node = (usize, usize);
 
-        for neighbour in heightmap.neighbours_orthogonal(vertex) {
-            if can_jump(vertex, neighbour) {
-                horizon.push_back(((cost + 1), neighbour));
-            }
-        }
-    }
+    fn neighbours(&self, &position: &(usize, usize)) -> Vec<((usize, usize), usize)> {
+        let Hill(heightmap) = self;
 
-    unreachable!()
+        heightmap
+            .neighbours_orthogonal(position)
+            .filter(|&neighbour| can_climb(heightmap[position], heightmap[neighbour]))
+            .map(|neighbour| (neighbour, 1))
+            .collect()
+    }
 }
 
-fn can_jump_to(heightmap: &Grid<char>, from: (usize, usize), to: (usize, usize)) -> bool {
-    (heightmap[to] as u8).saturating_sub(heightmap[from] as u8) <= 1
+/// The length of the climb, if there is one at all
+fn steps(path: Option<Path<(usize, usize)>>) -> Solution {
+    path.map_or_else(|| "no path".into(), |path| path.cost.into())
 }
 
 pub fn run(input: &str) -> (Solution, Solution) {
     let mut heightmap: Grid<char> = input.lines().map(|line| line.chars()).collect();
 
-    let mut start = None;
-    let mut end = None;
+    let start = heightmap.find(|&c| c == 'S').unwrap();
+    let end = heightmap.find(|&c| c == 'E').unwrap();
+    heightmap[start] = 'a';
+    heightmap[end] = 'z';
 
-    for y in 0..heightmap.height() {
-        for x in 0..heightmap.width() {
-            if heightmap[(x, y)] == 'S' {
-                start = Some((x, y));
-                heightmap[(x, y)] = 'a';
-            } else if heightmap[(x, y)] == 'E' {
-                end = Some((x, y));
-                heightmap[(x, y)] = 'z';
-            }
-        }
-    }
+    dump_image("day12_heightmap", &heightmap, &Heights::new('a', 'z'), 4);
 
-    let start = start.unwrap();
-    let end = end.unwrap();
+    let hill = Hill(&heightmap);
 
-    let result1 = solve(
-        &heightmap,
-        start,
-        |vertex, neighbour| can_jump_to(&heightmap, vertex, neighbour),
-        |vertex| vertex == end,
-    );
+    let result1 = hill.bfs([start], |&position| position == end);
 
-    // For part 2, just find the shortest path from the end to a vertex with height 'a'
-    // This requires flipping the jump condition
-    let result2 = solve(
-        &heightmap,
-        end,
-        |vertex, neighbour| can_jump_to(&heightmap, neighbour, vertex),
-        |vertex| heightmap[vertex] == 'a',
-    );
+    // For part 2, start from every square at the lowest height at once
+    let result2 = hill.bfs(heightmap.find_all(|&c| c == 'a'), |&position| {
+        position == end
+    });
 
-    (result1.into(), result2.into())
+    (steps(result1), steps(result2))
 }
 
 #[cfg(test)]
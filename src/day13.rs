@@ -5,6 +5,7 @@ use nom::{
     sequence::delimited, IResult,
 };
 
+use crate::parse;
 #[allow(unused)]
 use crate::prelude::*;
 
@@ -38,10 +39,6 @@ impl PartialOrd for Packet {
     }
 }
 
-fn parse_usize(input: &str) -> IResult<&str, usize> {
-    map(nom::character::complete::u64, |n| n as usize)(input)
-}
-
 fn parse_packet(input: &str) -> IResult<&str, Packet> {
     alt((
         map(parse_usize, Packet::Int),
@@ -57,8 +54,10 @@ pub fn run(input: &str) -> (Solution, Solution) {
         .split("\n\n")
         .map(|pair| {
             let (left, right) = pair.split_once('\n').unwrap();
-            let left = parse_packet(left).unwrap().1;
-            let right = parse_packet(right).unwrap().1;
+            let packet = |line| {
+                parse::finish(parse_packet(line)).unwrap_or_else(|error| error.report(input))
+            };
+            let (left, right) = (packet(left), packet(right));
             (left, right)
         })
         .collect();
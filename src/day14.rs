@@ -1,6 +1,7 @@
+use std::time::Duration;
+
 use nom::{bytes::complete::tag, multi::separated_list1, sequence::separated_pair, IResult};
 
-use crate::grid;
 #[allow(unused)]
 use crate::prelude::*;
 
@@ -23,24 +24,27 @@ fn unordered_line_iterator(
     Itertools::cartesian_product(x_range, y_range)
 }
 
-fn count_sand(cave: &mut Grid<char>, position: (usize, usize)) -> (usize, bool) {
-    if cave.get(position).is_none() {
-        return (0, true);
-    }
+/// Where the sand pours in from
+const SOURCE: Point<isize> = Point::new(500, 0);
 
+fn count_sand(
+    cave: &mut Grid<char>,
+    position: (usize, usize),
+    recording: &mut Recording<char>,
+) -> (usize, bool) {
     if cave[position] != '.' {
         return (0, false);
     }
 
-    let down = (position.0, position.1 + 1);
-    let down_left = (position.0.saturating_sub(1), position.1 + 1);
-    let down_right = (position.0 + 1, position.1 + 1);
-
     let mut total = 0;
-    let moves = [down, down_left, down_right];
 
-    for step in moves {
-        let (count, leaked) = count_sand(cave, step);
+    for direction in [Direction::Down, Direction::DownLeft, Direction::DownRight] {
+        // Falling off the map means falling into the abyss
+        let Some(step) = cave.offset(position, direction) else {
+            return (total, true);
+        };
+
+        let (count, leaked) = count_sand(cave, step, recording);
         total += count;
         if leaked {
             return (total, true);
@@ -48,61 +52,64 @@ fn count_sand(cave: &mut Grid<char>, position: (usize, usize)) -> (usize, bool)
     }
 
     cave[position] = 'o';
+    recording.record(cave);
     total += 1;
 
     (total, false)
 }
 
-pub fn run(input: &str) -> (Solution, Solution) {
-    let lines: Vec<_> = input
-        .lines()
-        .map(|line| parse_line(line).unwrap().1)
-        .collect();
-
-    let width = 1 + lines
-        .iter()
-        .flat_map(|line| line.iter().map(|&(x, _)| x))
-        .max()
-        .unwrap();
-
-    let height = 1 + lines
-        .iter()
-        .flat_map(|line| line.iter().map(|&(_, y)| y))
-        .max()
-        .unwrap();
+fn draw_rocks(lines: &[Vec<(usize, usize)>]) -> GridBuilder<char> {
+    let mut cave = GridBuilder::new('.');
+    cave.include(SOURCE);
 
-    let mut cave = grid!['.'; width, height];
-    for line in lines.iter() {
+    for line in lines {
         for segment in line.windows(2) {
             for point in unordered_line_iterator(segment[0], segment[1]) {
-                cave[point] = '#';
+                cave.set(Point::from(point).to_signed(), '#');
             }
         }
     }
 
-    let starting_point = (500, 0);
+    cave
+}
+
+pub fn run(input: &str) -> (Solution, Solution) {
+    let lines: Vec<_> = input
+        .lines()
+        .map(|line| parse_line(line).unwrap().1)
+        .collect();
 
     let result1 = {
-        let (count, leaked) = count_sand(&mut cave, starting_point);
+        let builder = draw_rocks(&lines);
+        let source = (SOURCE - builder.min()).to_unsigned().unwrap();
+        let mut cave = builder.build();
+
+        let mut recording = Recording::from_env("day14_part1", '.').every(10);
+        let (count, leaked) = count_sand(&mut cave, source.into(), &mut recording);
         assert!(leaked);
+        dump_image("day14_part1", &cave, &CharPalette, 4);
+        recording.finish(&CharPalette, 2, Duration::from_millis(20));
         count
     };
 
-    let mut cave2 = grid!['.'; width + height + 1, height + 2];
-    for line in lines {
-        for segment in line.windows(2) {
-            for point in unordered_line_iterator(segment[0], segment[1]) {
-                cave2[point] = '#';
-            }
+    let result2 = {
+        let mut builder = draw_rocks(&lines);
+
+        // Sand spreads at most one square sideways for every square it falls, so it piles up in
+        // a triangle that the floor only needs to cover
+        let floor = 2 + lines.iter().flatten().map(|&(_, y)| y).max().unwrap() as isize;
+        for x in SOURCE.x - floor..=SOURCE.x + floor {
+            builder.set(Point::new(x, floor), '#');
         }
-    }
-    for x in 0..cave2.width() {
-        cave2[(x, height + 1)] = '#';
-    }
 
-    let result2 = {
-        let (count, leaked) = count_sand(&mut cave2, starting_point);
+        let source = (SOURCE - builder.min()).to_unsigned().unwrap();
+        let mut cave = builder.build();
+
+        let mut recording = Recording::from_env("day14_part2", '.').every(250);
+        let (count, leaked) = count_sand(&mut cave, source.into(), &mut recording);
         assert!(!leaked);
+        dump_image("day14_part2", &cave, &CharPalette, 4);
+        recording.finish(&CharPalette, 2, Duration::from_millis(20));
         count
     };
 
@@ -7,6 +7,12 @@ use nom::{
 
 #[allow(unused)]
 use crate::prelude::*;
+use crate::{
+    bitset::BitSet128,
+    branch_and_bound::{maximise_with_memo, Problem},
+    memo::{KeyEncoder, Memo, Store},
+    search,
+};
 
 #[derive(Debug)]
 struct Valve<Label> {
@@ -15,36 +21,7 @@ struct Valve<Label> {
     tunnels: Vec<Label>,
 }
 
-struct Cache {
-    store: Vec<Option<u16>>,
-}
-
-impl Cache {
-    fn new() -> Self {
-        let max_cache_entries = ((1 << 6) - 1) // valve label
-        * ((1 << 5) - 1) // time remaining
-        * ((1 << 15) - 1) // visited set
-        * 4; // I'm gonna be honest, idk where this came from
-
-        Self {
-            store: vec![None; max_cache_entries],
-        }
-    }
-
-    fn index(label: u8, time_left: u8, activated: u16) -> usize {
-        ((activated as usize) << 11) | ((label as usize) << 5) | (time_left as usize)
-    }
-
-    fn get(&self, label: u8, time_left: u8, activated: u16) -> Option<u16> {
-        self.store[Self::index(label, time_left, activated)]
-    }
-
-    fn insert(&mut self, label: u8, time_left: u8, activated: u16, value: u16) {
-        self.store[Self::index(label, time_left, activated)] = Some(value);
-    }
-}
-
-fn parse_line<'a>(input: &'a str) -> IResult<&'a str, Valve<&'a str>> {
+fn parse_line(input: &str) -> IResult<&str, Valve<&str>> {
     map(
         tuple((
             tag("Valve "),
@@ -68,46 +45,170 @@ fn parse_line<'a>(input: &'a str) -> IResult<&'a str, Valve<&'a str>> {
 const TIME: u8 = 30;
 const TIME_2: u8 = 26;
 
-fn solve(
-    label: u8,
+/// The valves worth opening, plus the start, with the time it takes to walk between any two
+struct Network {
+    rates: Vec<u16>,
+    distances: Vec<Vec<u8>>,
+    start: u8,
+}
+
+/// Some agents taking turns to tour the network from the start, each with the same time to do it
+/// in and leaving the valves the ones before them opened alone
+struct Tours<'a> {
+    network: &'a Network,
+    agents: u8,
+    time: u8,
+}
+
+#[derive(Debug, Clone)]
+struct State {
+    /// How many agents have already finished their tours
+    agent: u8,
+    /// Where the current agent last opened a valve (or started), and the time it had left then
+    site: u8,
     time_left: u8,
-    activated_set: u16,
-    valves: &[Valve<u8>],
-    cache: &mut Cache,
-) -> u16 {
-    if time_left == 0 {
-        return 0;
+    opened: BitSet128,
+    /// Total pressure the opened valves will release by the end
+    released: u16,
+}
+
+impl Tours<'_> {
+    /// The time left after walking from `site` to `valve` and opening it
+    fn time_after(&self, site: u8, time_left: u8, valve: usize) -> u8 {
+        time_left.saturating_sub(self.network.distances[site as usize][valve] + 1)
     }
+}
 
-    if let Some(cache_entry) = cache.get(label, time_left, activated_set) {
-        return cache_entry;
+impl Problem for Tours<'_> {
+    type State = State;
+
+    fn successors(&self, state: &State) -> Vec<State> {
+        let rates = &self.network.rates;
+        let mut successors = state
+            .opened
+            .complement(rates.len())
+            .into_iter()
+            .filter(|&valve| rates[valve] > 0)
+            .filter_map(|valve| {
+                let time_left = self.time_after(state.site, state.time_left, valve);
+                (time_left > 0).then(|| {
+                    let mut next = state.clone();
+                    next.site = valve as u8;
+                    next.time_left = time_left;
+                    next.opened.insert(valve);
+                    next.released += rates[valve] * time_left as u16;
+                    next
+                })
+            })
+            .sorted_by_key(|next| Reverse(next.released))
+            .collect_vec();
+
+        // Or stop here, leaving the rest to the next agent
+        if state.agent + 1 < self.agents {
+            successors.push(State {
+                agent: state.agent + 1,
+                site: self.network.start,
+                time_left: self.time,
+                ..state.clone()
+            });
+        }
+
+        successors
+    }
+
+    fn value(&self, state: &State) -> usize {
+        state.released as usize
     }
 
-    let current_valve = &valves[label as usize];
+    fn bound(&self, state: &State) -> usize {
+        // Every closed valve opened as early as this agent or any still to come could get there
+        let more_agents = state.agent + 1 < self.agents;
+        let unopened = state
+            .opened
+            .complement(self.network.rates.len())
+            .into_iter()
+            .map(|valve| {
+                let mut time_left = self.time_after(state.site, state.time_left, valve);
+                if more_agents {
+                    time_left =
+                        time_left.max(self.time_after(self.network.start, self.time, valve));
+                }
+                self.network.rates[valve] as usize * time_left as usize
+            })
+            .sum::<usize>();
+
+        state.released as usize + unopened
+    }
+}
+
+/// Packs a state's `(agent, site, time_left, opened)` into a memo index. The valves worth opening
+/// come first in the network, so the opened set fits in one bit for each of them.
+struct StateEncoder {
+    agents: usize,
+    sites: usize,
+    time: usize,
+    useful_valves: usize,
+}
 
-    let mut best = 0;
+impl KeyEncoder for StateEncoder {
+    type Key = (u8, u8, u8, BitSet128);
 
-    if current_valve.rate > 0 && activated_set & (1 << label) == 0 {
-        let new_set = activated_set | (1 << label);
-        let time_left = time_left - 1;
+    fn bound(&self) -> usize {
+        (self.agents * self.sites * (self.time + 1)) << self.useful_valves
+    }
 
-        let subresult = solve(label, time_left, new_set, valves, cache);
-        best = best.max(subresult + (time_left as u16) * (current_valve.rate as u16));
+    fn encode(&self, &(agent, site, time_left, opened): &Self::Key) -> usize {
+        let opened = opened.iter().fold(0, |bits, valve| bits | 1 << valve);
+        ((opened * self.agents + agent as usize) * self.sites + site as usize) * (self.time + 1)
+            + time_left as usize
     }
+}
+
+/// Dense memo tables with more slots than this take more memory than they're worth
+const MAX_DENSE_SLOTS: usize = 1 << 25;
+
+fn most_pressure(network: &Network, agents: u8, time: u8) -> usize {
+    let tours = Tours {
+        network,
+        agents,
+        time,
+    };
+    let encoder = StateEncoder {
+        agents: agents as usize,
+        sites: network.rates.len(),
+        time: time as usize,
+        useful_valves: network.rates.iter().filter(|&&rate| rate > 0).count(),
+    };
 
-    for &neighbour in current_valve.tunnels.iter() {
-        best = best.max(solve(
-            neighbour,
-            time_left - 1,
-            activated_set,
-            valves,
-            cache,
-        ));
+    // Too many valves for a dense table is checked first, so the bound can't overflow
+    let dense = encoder.useful_valves <= MAX_DENSE_SLOTS.trailing_zeros() as usize
+        && encoder.bound() <= MAX_DENSE_SLOTS;
+    if dense {
+        most_pressure_with(&tours, &mut Memo::dense(encoder))
+    } else {
+        most_pressure_with(&tours, &mut Memo::hashed())
     }
+}
 
-    cache.insert(label, time_left, activated_set, best);
+fn most_pressure_with<S>(tours: &Tours, memo: &mut Memo<S>) -> usize
+where
+    S: Store<Key = (u8, u8, u8, BitSet128), Value = u16>,
+{
+    let outcome = maximise_with_memo(
+        tours,
+        State {
+            agent: 0,
+            site: tours.network.start,
+            time_left: tours.time,
+            opened: BitSet128::EMPTY,
+            released: 0,
+        },
+        memo,
+        |state| (state.agent, state.site, state.time_left, state.opened),
+    );
+    memo.report("day16");
 
-    best
+    outcome.value
 }
 
 pub fn run(input: &str) -> (Solution, Solution) {
@@ -135,19 +236,41 @@ pub fn run(input: &str) -> (Solution, Solution) {
 
     let aa = valves_lookup.get("AA").unwrap().0;
 
-    let mut cache = Cache::new();
-
-    let result1 = solve(aa, TIME, 0, &valves, &mut cache) as usize;
+    // Valves are sorted by rate, so the ones worth opening come first
+    let sites = valves
+        .iter()
+        .filter(|valve| valve.rate > 0 || valve.label == aa)
+        .map(|valve| valve.label)
+        .collect_vec();
+    let start = sites.iter().position(|&site| site == aa).unwrap() as u8;
 
-    let result2 = (0u16..((1 << 15) - 1))
-        .map(|me| {
-            let elephant = !me;
+    let network = Network {
+        rates: sites
+            .iter()
+            .map(|&site| valves[site as usize].rate as u16)
+            .collect(),
+        distances: sites
+            .iter()
+            .map(|&from| {
+                sites
+                    .iter()
+                    .map(|&to| {
+                        search::bfs(
+                            [from],
+                            |&valve| valves[valve as usize].tunnels.iter().copied(),
+                            |&valve| valve == to,
+                        )
+                        .unwrap()
+                        .cost as u8
+                    })
+                    .collect()
+            })
+            .collect(),
+        start,
+    };
 
-            solve(aa, TIME_2, me, &valves, &mut cache)
-                + solve(aa, TIME_2, elephant, &valves, &mut cache)
-        })
-        .max()
-        .unwrap() as usize;
+    let result1 = most_pressure(&network, 1, TIME);
+    let result2 = most_pressure(&network, 2, TIME_2);
 
     (result1.into(), result2.into())
 }
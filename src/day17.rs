@@ -1,5 +1,8 @@
+use std::time::Duration;
+
 use nom::{branch::alt, bytes::complete::tag, combinator::value, multi::many1, IResult};
 
+use crate::cycle::find_cycle;
 #[allow(unused)]
 use crate::prelude::*;
 
@@ -16,7 +19,7 @@ fn parse_input(input: &str) -> IResult<&str, Vec<Shift>> {
     )))(input)
 }
 
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy)]
 enum Piece {
     Horizontal,
     Plus,
@@ -42,14 +45,14 @@ impl Piece {
     }
 }
 
-fn shift_left(piece: Vec<(usize, usize)>, chamber: &[u8]) -> Vec<(usize, usize)> {
+fn shift_left(piece: Vec<(usize, usize)>, chamber: &BitGrid) -> Vec<(usize, usize)> {
     if let Some(next_piece) = piece
         .iter()
         .copied()
         .map(|(x, y)| {
             let x_next = x.checked_sub(1)?;
 
-            if chamber[y] & (1 << x_next) == 0 {
+            if !chamber.get((x_next, y)) {
                 Some((x_next, y))
             } else {
                 None
@@ -63,14 +66,14 @@ fn shift_left(piece: Vec<(usize, usize)>, chamber: &[u8]) -> Vec<(usize, usize)>
     }
 }
 
-fn shift_right(piece: Vec<(usize, usize)>, chamber: &[u8]) -> Vec<(usize, usize)> {
+fn shift_right(piece: Vec<(usize, usize)>, chamber: &BitGrid) -> Vec<(usize, usize)> {
     if let Some(next_piece) = piece
         .iter()
         .copied()
         .map(|(x, y)| {
             let x_next = x.checked_add(1)?;
 
-            if x_next < 7 && chamber[y] & (1 << x_next) == 0 {
+            if x_next < 7 && !chamber.get((x_next, y)) {
                 Some((x_next, y))
             } else {
                 None
@@ -84,133 +87,141 @@ fn shift_right(piece: Vec<(usize, usize)>, chamber: &[u8]) -> Vec<(usize, usize)
     }
 }
 
-fn shift_down(
-    piece: Vec<(usize, usize)>,
-    chamber: &[u8],
-) -> Result<Vec<(usize, usize)>, Vec<(usize, usize)>> {
-    if let Some(next_piece) = piece
+/// The piece one row lower, or `None` if it has landed
+fn shift_down(piece: &[(usize, usize)], chamber: &BitGrid) -> Option<Vec<(usize, usize)>> {
+    piece
         .iter()
         .copied()
         .map(|(x, y)| {
             let y_next = y.checked_sub(1)?;
 
-            if chamber[y_next] & (1 << x) == 0 {
+            if !chamber.get((x, y_next)) {
                 Some((x, y_next))
             } else {
                 None
             }
         })
         .collect()
-    {
-        Ok(next_piece)
-    } else {
-        Err(piece)
-    }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-struct Key {
-    piece: Piece,
-    jet_index: usize,
+/// The top of the tower, with the floor at the bottom like in the puzzle's drawings
+fn tower_top(chamber: &BitGrid, height: usize) -> Grid<bool> {
+    const ROWS: usize = 40;
+
+    (0..ROWS)
+        .map(|row| {
+            let y = (height + 4).checked_sub(row);
+            (0..7).map(move |x| y.is_some_and(|y| chamber.get((x, y))))
+        })
+        .collect()
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-struct Value {
+const PIECES: [Piece; 5] = [
+    Piece::Horizontal,
+    Piece::Plus,
+    Piece::Angle,
+    Piece::Vertical,
+    Piece::Square,
+];
+
+struct Tower<'a> {
+    jets: &'a [Shift],
+    /// The next jet to push a piece
+    jet_index: usize,
+    pieces_dropped: usize,
+    chamber: BitGrid,
     height: usize,
-    piece_index: usize,
 }
 
-pub fn run(input: &str) -> (Solution, Solution) {
-    let shifts = parse_input(input).unwrap().1;
-    let mut jets = shifts.iter().copied().enumerate().cycle();
-
-    let pieces = [
-        Piece::Horizontal,
-        Piece::Plus,
-        Piece::Angle,
-        Piece::Vertical,
-        Piece::Square,
-    ]
-    .into_iter()
-    .cycle();
+impl<'a> Tower<'a> {
+    fn new(jets: &'a [Shift]) -> Self {
+        Self {
+            jets,
+            jet_index: 0,
+            pieces_dropped: 0,
+            chamber: BitGrid::new(7, 8),
+            height: 0,
+        }
+    }
 
-    let mut height = 0;
+    fn drop_piece(&mut self) {
+        // In the worst case, the tallest piece is 4 units tall, and has an initial gap of 3
+        if self.chamber.height() < self.height + 8 {
+            self.chamber.resize_rows(2 * (self.height + 8));
+        }
 
-    const NUM_PIECES: usize = 2022;
+        let piece = PIECES[self.pieces_dropped % PIECES.len()];
+        let mut squares = piece.initial_squares(self.height);
 
-    // In the worst case, the tallest piece is 4 units tall, and has an initial gap of 3
-    let mut chamber: Vec<u8> = vec![0; 7 * NUM_PIECES];
+        loop {
+            let shift = self.jets[self.jet_index];
+            self.jet_index = (self.jet_index + 1) % self.jets.len();
 
-    let mut cache = HashMap::<Key, Vec<Value>>::default();
+            squares = match shift {
+                Shift::Left => shift_left(squares, &self.chamber),
+                Shift::Right => shift_right(squares, &self.chamber),
+            };
 
-    let result1 = {
-        for (piece_index, piece) in pieces.take(NUM_PIECES).enumerate() {
-            let mut squares = piece.initial_squares(height);
-
-            let piece_index = piece_index + 1;
-            let mut final_jet_index = None;
-
-            for (i, shift) in jets.by_ref() {
-                final_jet_index = Some(i);
-
-                squares = match shift {
-                    Shift::Left => shift_left(squares, &chamber),
-                    Shift::Right => shift_right(squares, &chamber),
-                };
-
-                match shift_down(squares, &chamber) {
-                    Ok(next_squares) => squares = next_squares,
-                    Err(old_squares) => {
-                        squares = old_squares;
-                        break;
-                    }
-                }
+            match shift_down(&squares, &self.chamber) {
+                Some(next_squares) => squares = next_squares,
+                None => break,
             }
+        }
 
-            height = height.max(squares.iter().map(|&(_, y)| y).max().unwrap());
-
-            cache
-                .entry(Key {
-                    piece,
-                    jet_index: final_jet_index.unwrap(),
-                })
-                .or_insert_with(Vec::new)
-                .push(Value {
-                    height,
-                    piece_index,
-                });
-
-            for (x, y) in squares {
-                chamber[y] |= 1 << x;
-            }
+        self.height = self
+            .height
+            .max(squares.iter().map(|&(_, y)| y).max().unwrap());
+        for (x, y) in squares {
+            self.chamber.set((x, y), true);
         }
+        self.pieces_dropped += 1;
+    }
 
-        height
-    };
+    /// Everything that decides how the next pieces fall: which piece and jet come next, and the
+    /// shape of the top of the tower. Pieces can't fall further than this into the tower in
+    /// practice.
+    fn fingerprint(&self) -> (usize, usize, Vec<u64>) {
+        const ROWS: usize = 32;
 
-    const PART_2_NUM_PIECES: usize = 1_000_000_000_000;
+        let rows = (self.height.saturating_sub(ROWS)..=self.height)
+            .map(|y| self.chamber.row(y)[0])
+            .collect();
+        (self.pieces_dropped % PIECES.len(), self.jet_index, rows)
+    }
+}
 
-    let result2 = cache
-        .into_iter()
-        .find_map(|(_, values)| {
-            if values.len() < 2 {
-                return None;
-            };
+pub fn run(input: &str) -> (Solution, Solution) {
+    let shifts = parse_input(input).unwrap().1;
 
-            let step = values[1].piece_index - values[0].piece_index;
-            let start = values[0].piece_index;
+    const NUM_PIECES: usize = 2022;
 
-            if (PART_2_NUM_PIECES - start) % step == 0 {
-                let start_height = values[0].height;
-                let step_height = values[1].height - values[0].height;
+    let mut recording = Recording::from_env("day17", false);
 
-                let num_steps = (PART_2_NUM_PIECES - start) / step;
-                Some(start_height + num_steps * step_height)
-            } else {
-                None
-            }
-        })
-        .unwrap();
+    let result1 = {
+        let mut tower = Tower::new(&shifts);
+        for _ in 0..NUM_PIECES {
+            tower.drop_piece();
+            recording.record_with(|| tower_top(&tower.chamber, tower.height));
+        }
+
+        let palette = BoolPalette {
+            on: [120, 120, 140],
+            off: [20, 20, 30],
+        };
+        recording.finish(&palette, 8, Duration::from_millis(40));
+
+        tower.height
+    };
+
+    const PART_2_NUM_PIECES: usize = 1_000_000_000_000;
+
+    let result2 = find_cycle(
+        Tower::new(&shifts),
+        Tower::drop_piece,
+        Tower::fingerprint,
+        |tower| tower.height,
+    )
+    .extrapolate(PART_2_NUM_PIECES);
 
     (result1.into(), result2.into())
 }
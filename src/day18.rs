@@ -1,163 +1,56 @@
-use std::{
-    collections::VecDeque,
-    ops::{Index, IndexMut},
-};
-
+use crate::input;
+use crate::parse::{self, ParseError};
 #[allow(unused)]
 use crate::prelude::*;
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum Fill {
     Air,
-    Water,
     Lava,
 }
 
-struct Space {
-    min: (isize, isize, isize),
-    max: (isize, isize, isize),
-    space: Vec<Fill>,
-}
-
-impl Space {
-    fn x_span(&self) -> usize {
-        (self.max.0 - self.min.0 + 1) as usize
-    }
-
-    fn y_span(&self) -> usize {
-        (self.max.1 - self.min.1 + 1) as usize
-    }
-
-    fn z_span(&self) -> usize {
-        (self.max.2 - self.min.2 + 1) as usize
-    }
-
-    fn len(&self) -> usize {
-        self.space.len()
-    }
-
-    fn new(min: (isize, isize, isize), max: (isize, isize, isize)) -> Self {
-        let mut this = Self {
-            min,
-            max,
-            space: Vec::new(),
-        };
-
-        this.space
-            .resize(this.x_span() * this.y_span() * this.z_span(), Fill::Air);
-
-        this
-    }
-
-    fn neighbours(
-        &self,
-        (x, y, z): (isize, isize, isize),
-    ) -> impl Iterator<Item = (isize, isize, isize)> {
-        let (min_x, min_y, min_z) = self.min;
-        let (max_x, max_y, max_z) = self.max;
-
-        [-1, 1]
-            .into_iter()
-            .flat_map(move |shift| [(x + shift, y, z), (x, y + shift, z), (x, y, z + shift)])
-            .filter(move |(x, y, z)| {
-                (min_x..=max_x).contains(&x)
-                    && (min_y..=max_y).contains(&y)
-                    && (min_z..=max_z).contains(&z)
-            })
-    }
-}
-
-impl Index<(isize, isize, isize)> for Space {
-    type Output = Fill;
-
-    fn index(&self, (x, y, z): (isize, isize, isize)) -> &Self::Output {
-        let (min_x, min_y, min_z) = self.min;
-        let x = (x - min_x) as usize;
-        let y = (y - min_y) as usize;
-        let z = (z - min_z) as usize;
-        let x_span = self.x_span();
-        let y_span = self.y_span();
-
-        &self.space[z * y_span * x_span + y * x_span + x]
-    }
-}
-
-impl IndexMut<(isize, isize, isize)> for Space {
-    fn index_mut(&mut self, (x, y, z): (isize, isize, isize)) -> &mut Self::Output {
-        let (min_x, min_y, min_z) = self.min;
-        let x = (x - min_x) as usize;
-        let y = (y - min_y) as usize;
-        let z = (z - min_z) as usize;
-        let x_span = self.x_span();
-        let y_span = self.y_span();
-
-        &mut self.space[z * y_span * x_span + y * x_span + x]
-    }
-}
-
-fn parse_int(mut bytes: &[u8], delimiter: u8) -> (&[u8], isize) {
-    let mut num: isize = 0;
-
-    while bytes[0] != delimiter {
-        num *= 10;
-        num += (bytes[0] - b'0') as isize;
-        bytes = &bytes[1..];
-    }
-
-    (&bytes[1..], num)
+/// A cube like `2,-1,5`
+fn parse_cube(line: &[u8]) -> Result<[isize; 3], ParseError<'_>> {
+    let (line, x) = parse::int_until(line, b',')?;
+    let (line, y) = parse::int_until(line, b',')?;
+    let z = parse::int_exact(line)?;
+    Ok([x, y, z])
 }
 
 pub fn run(input: &str) -> (Solution, Solution) {
-    let mut coords = Vec::new();
-    let mut bytes = input.as_bytes();
-
-    while !bytes.is_empty() {
-        let (x, y, z);
-        (bytes, x) = parse_int(bytes, b',');
-        (bytes, y) = parse_int(bytes, b',');
-        (bytes, z) = parse_int(bytes, b'\n');
-        coords.push((x, y, z));
-    }
-
-    let min_x = coords.iter().copied().map(|(x, _, _)| x).min().unwrap() - 1;
-    let min_y = coords.iter().copied().map(|(_, y, _)| y).min().unwrap() - 1;
-    let min_z = coords.iter().copied().map(|(_, _, z)| z).min().unwrap() - 1;
-    let min = (min_x, min_y, min_z);
-
-    let max_x = coords.iter().copied().map(|(x, _, _)| x).max().unwrap() + 1;
-    let max_y = coords.iter().copied().map(|(_, y, _)| y).max().unwrap() + 1;
-    let max_z = coords.iter().copied().map(|(_, _, z)| z).max().unwrap() + 1;
-    let max = (max_x, max_y, max_z);
+    let coords: Vec<[isize; 3]> = input::lines(input.as_bytes())
+        .filter(|line| !line.is_empty())
+        .map(|line| parse_cube(line).unwrap_or_else(|error| error.report(input)))
+        .collect();
 
-    let mut space = Space::new(min, max);
+    let min = std::array::from_fn(|axis| coords.iter().map(|c| c[axis]).min().unwrap());
+    let max = std::array::from_fn(|axis| coords.iter().map(|c| c[axis]).max().unwrap() + 1);
 
+    let mut droplet = Grid3::new_filled(Fill::Air, min, max);
     for coord in coords.iter().copied() {
-        space[coord] = Fill::Lava;
+        droplet[coord] = Fill::Lava;
     }
 
+    // Leave a layer of air all around the droplet so the water can flow everywhere outside it
+    let space = droplet.pad(1, Fill::Air);
+
     let result1 = coords
         .into_iter()
-        .flat_map(|coord| space.neighbours(coord))
+        .flat_map(|coord| space.face_neighbours(coord))
         .filter(|&neighbour| space[neighbour] != Fill::Lava)
         .count();
 
-    let mut queue = VecDeque::with_capacity(space.len());
-    queue.push_back(min);
-
-    let mut result2 = 0;
+    // Flood the space around the droplet, then count the lava faces the water touches
+    let water = space.flood_fill(space.min(), Neighbourhood::Orthogonal, |_, &to| {
+        to != Fill::Lava
+    });
 
-    while let Some(coord) = queue.pop_front() {
-        for neighbour in space.neighbours(coord) {
-            match space[neighbour] {
-                Fill::Lava => result2 += 1,
-                Fill::Air => {
-                    queue.push_back(neighbour);
-                    space[neighbour] = Fill::Water;
-                }
-                Fill::Water => (),
-            }
-        }
-    }
+    let result2 = space
+        .positions()
+        .filter(|&position| water[position].is_some())
+        .flat_map(|position| space.face_neighbours(position))
+        .filter(|&neighbour| space[neighbour] == Fill::Lava)
+        .count();
 
     (result1.into(), result2.into())
 }
@@ -1,5 +1,7 @@
 use nom::{bytes::complete::tag, combinator::map, sequence::tuple, IResult};
 
+use crate::branch_and_bound::{maximise, Problem};
+use crate::math::checked_div_ceil;
 #[allow(unused)]
 use crate::prelude::*;
 
@@ -84,181 +86,154 @@ struct Resources {
     geode: usize,
 }
 
-trait CheckedDivCeil: Sized {
-    fn checked_div_ceil(self, other: Self) -> Option<Self>;
-}
-
-impl CheckedDivCeil for usize {
-    fn checked_div_ceil(self, other: Self) -> Option<Self> {
-        let remainder = self.checked_rem(other)?;
-        let quotient = self.checked_div(other)?;
-
-        Some(if remainder != 0 {
-            quotient + 1
-        } else {
-            quotient
-        })
-    }
+impl Resources {
+    const NONE: Resources = Resources {
+        ore: 0,
+        clay: 0,
+        obsidian: 0,
+        geode: 0,
+    };
 }
 
-fn brute_force(
-    mut best: usize,
-    cache: &mut HashMap<(Resources, usize, Resources), usize>,
-    blueprint: Blueprint,
+#[derive(Debug, Clone, Copy)]
+struct Mining {
     robots: Resources,
-    time_left: usize,
     resources: Resources,
-) -> usize {
-    if time_left == 0 {
-        return resources.geode;
-    }
+    time_left: usize,
+}
 
-    if let Some(entry) = cache.get(&(robots, time_left, resources)) {
-        return *entry;
-    }
+/// Minutes until there's `cost` of a resource, starting with `have` and mining `rate` a minute
+fn wait(cost: usize, have: usize, rate: usize) -> Option<usize> {
+    checked_div_ceil(cost.saturating_sub(have), rate).or((have >= cost).then_some(0))
+}
 
-    if best
-        >= robots.geode * time_left + resources.geode + time_left * time_left.saturating_sub(1) / 2
-    {
-        return 0;
+/// Wait until the robot can be afforded, then build it
+fn build(state: &Mining, robot: Resources, cost: Resources) -> Option<Mining> {
+    let Mining {
+        robots,
+        resources,
+        time_left,
+    } = *state;
+
+    let time_needed = [
+        wait(cost.ore, resources.ore, robots.ore)?,
+        wait(cost.clay, resources.clay, robots.clay)?,
+        wait(cost.obsidian, resources.obsidian, robots.obsidian)?,
+    ]
+    .into_iter()
+    .max()
+    .unwrap()
+        + 1;
+
+    if time_left < time_needed {
+        return None;
     }
 
-    let mut inner = || {
-        let next_resources = |time_delta| Resources {
-            ore: resources.ore + robots.ore * time_delta,
-            clay: resources.clay + robots.clay * time_delta,
-            obsidian: resources.obsidian + robots.obsidian * time_delta,
-            geode: resources.geode + robots.geode * time_delta,
-        };
-
-        {
-            let ore_needed = blueprint.geode.0.saturating_sub(resources.ore);
-            let obsidian_needed = blueprint.geode.1.saturating_sub(resources.obsidian);
-
-            let time_to_ore = ore_needed
-                .checked_div_ceil(robots.ore)
-                .unwrap_or(usize::MAX);
-            let time_to_obsidian = obsidian_needed
-                .checked_div_ceil(robots.obsidian)
-                .unwrap_or(usize::MAX);
-            let time_needed = usize::max(time_to_ore, time_to_obsidian);
-            let time_needed = time_needed.saturating_add(1);
-
-            if time_left >= time_needed {
-                let next_resources = next_resources(time_needed);
-
-                best = best.max(brute_force(
-                    best,
-                    cache,
-                    blueprint,
-                    Resources {
-                        geode: robots.geode + 1,
-                        ..robots
-                    },
-                    time_left - time_needed,
-                    Resources {
-                        ore: next_resources.ore - blueprint.geode.0,
-                        obsidian: next_resources.obsidian - blueprint.geode.1,
-                        ..next_resources
-                    },
-                ));
-            }
-        }
-
-        if robots.obsidian < blueprint.most_obsidian_needed() {
-            let ore_needed = blueprint.obsidian.0.saturating_sub(resources.ore);
-            let clay_needed = blueprint.obsidian.1.saturating_sub(resources.clay);
-
-            let time_to_ore = ore_needed
-                .checked_div_ceil(robots.ore)
-                .unwrap_or(usize::MAX);
-            let time_to_clay = clay_needed
-                .checked_div_ceil(robots.clay)
-                .unwrap_or(usize::MAX);
-            let time_needed = usize::max(time_to_ore, time_to_clay);
-            let time_needed = time_needed.saturating_add(1);
-
-            if time_left >= time_needed {
-                let next_resources = next_resources(time_needed);
-
-                best = best.max(brute_force(
-                    best,
-                    cache,
-                    blueprint,
-                    Resources {
-                        obsidian: robots.obsidian + 1,
-                        ..robots
-                    },
-                    time_left - time_needed,
-                    Resources {
-                        ore: next_resources.ore - blueprint.obsidian.0,
-                        clay: next_resources.clay - blueprint.obsidian.1,
-                        ..next_resources
-                    },
-                ));
-            }
-        }
-
-        if robots.clay < blueprint.most_clay_needed() {
-            let ore_needed = blueprint.clay.saturating_sub(resources.ore);
-            let time_needed = ore_needed
-                .checked_div_ceil(robots.ore)
-                .unwrap_or(usize::MAX);
-            let time_needed = time_needed.saturating_add(1);
-
-            if time_left >= time_needed {
-                let next_resources = next_resources(time_needed);
-
-                best = best.max(brute_force(
-                    best,
-                    cache,
-                    blueprint,
-                    Resources {
-                        clay: robots.clay + 1,
-                        ..robots
-                    },
-                    time_left - time_needed,
-                    Resources {
-                        ore: next_resources.ore - blueprint.clay,
-                        ..next_resources
-                    },
-                ));
-            }
-        }
+    Some(Mining {
+        robots: Resources {
+            ore: robots.ore + robot.ore,
+            clay: robots.clay + robot.clay,
+            obsidian: robots.obsidian + robot.obsidian,
+            geode: robots.geode + robot.geode,
+        },
+        resources: Resources {
+            ore: resources.ore + robots.ore * time_needed - cost.ore,
+            clay: resources.clay + robots.clay * time_needed - cost.clay,
+            obsidian: resources.obsidian + robots.obsidian * time_needed - cost.obsidian,
+            geode: resources.geode + robots.geode * time_needed,
+        },
+        time_left: time_left - time_needed,
+    })
+}
 
-        if robots.ore < blueprint.most_ore_needed() {
-            let ore_needed = blueprint.ore.saturating_sub(resources.ore);
-            let time_needed = ore_needed
-                .checked_div_ceil(robots.ore)
-                .unwrap_or(usize::MAX);
-            let time_needed = time_needed.saturating_add(1);
+impl Problem for Blueprint {
+    type State = Mining;
+
+    /// Every robot worth building next, best first. There's no point in having more robots of a
+    /// kind than the most of that resource any robot costs, since only one robot is built a minute.
+    fn successors(&self, state: &Mining) -> Vec<Mining> {
+        let robots = state.robots;
+
+        [
+            (
+                Resources {
+                    geode: 1,
+                    ..Resources::NONE
+                },
+                Resources {
+                    ore: self.geode.0,
+                    obsidian: self.geode.1,
+                    ..Resources::NONE
+                },
+                true,
+            ),
+            (
+                Resources {
+                    obsidian: 1,
+                    ..Resources::NONE
+                },
+                Resources {
+                    ore: self.obsidian.0,
+                    clay: self.obsidian.1,
+                    ..Resources::NONE
+                },
+                robots.obsidian < self.most_obsidian_needed(),
+            ),
+            (
+                Resources {
+                    clay: 1,
+                    ..Resources::NONE
+                },
+                Resources {
+                    ore: self.clay,
+                    ..Resources::NONE
+                },
+                robots.clay < self.most_clay_needed(),
+            ),
+            (
+                Resources {
+                    ore: 1,
+                    ..Resources::NONE
+                },
+                Resources {
+                    ore: self.ore,
+                    ..Resources::NONE
+                },
+                robots.ore < self.most_ore_needed(),
+            ),
+        ]
+        .into_iter()
+        .filter(|&(_, _, worth_building)| worth_building)
+        .filter_map(|(robot, cost, _)| build(state, robot, cost))
+        .collect()
+    }
 
-            if time_left >= time_needed {
-                let next_resources = next_resources(time_needed);
+    /// The geodes there will be if no more robots are built
+    fn value(&self, state: &Mining) -> usize {
+        state.resources.geode + state.robots.geode * state.time_left
+    }
 
-                best = best.max(brute_force(
-                    best,
-                    cache,
-                    blueprint,
-                    Resources {
-                        ore: robots.ore + 1,
-                        ..robots
-                    },
-                    time_left - time_needed,
-                    Resources {
-                        ore: next_resources.ore - blueprint.ore,
-                        ..next_resources
-                    },
-                ));
-            }
-        }
+    /// As if a geode robot were built every minute from now on
+    fn bound(&self, state: &Mining) -> usize {
+        self.value(state) + state.time_left * state.time_left.saturating_sub(1) / 2
+    }
+}
 
-        best
-    };
+fn most_geodes(blueprint: &Blueprint, minutes: usize) -> usize {
+    // The bound alone prunes well enough here that remembering states costs more than it saves
+    let outcome = maximise(
+        blueprint,
+        Mining {
+            robots: Resources {
+                ore: 1,
+                ..Resources::NONE
+            },
+            resources: Resources::NONE,
+            time_left: minutes,
+        },
+    );
 
-    let result = inner();
-    cache.insert((robots, time_left, resources), result);
-    return result;
+    outcome.value
 }
 
 pub fn run(input: &str) -> (Solution, Solution) {
@@ -267,63 +242,16 @@ pub fn run(input: &str) -> (Solution, Solution) {
         .map(|line| parse_line(line).unwrap().1)
         .collect();
 
-    let result1 = {
-        blueprints
-            .iter()
-            .copied()
-            .map(|blueprint| {
-                let mut cache = HashMap::default();
-                blueprint.id
-                    * brute_force(
-                        0,
-                        &mut cache,
-                        blueprint,
-                        Resources {
-                            ore: 1,
-                            clay: 0,
-                            obsidian: 0,
-                            geode: 0,
-                        },
-                        24,
-                        Resources {
-                            ore: 0,
-                            clay: 0,
-                            obsidian: 0,
-                            geode: 0,
-                        },
-                    )
-            })
-            .sum::<usize>()
-    };
+    let result1 = blueprints
+        .iter()
+        .map(|blueprint| blueprint.id * most_geodes(blueprint, 24))
+        .sum::<usize>();
 
-    let result2 = {
-        blueprints
-            .iter()
-            .take(3)
-            .copied()
-            .map(|blueprint| {
-                let mut cache = HashMap::default();
-                brute_force(
-                    0,
-                    &mut cache,
-                    blueprint,
-                    Resources {
-                        ore: 1,
-                        clay: 0,
-                        obsidian: 0,
-                        geode: 0,
-                    },
-                    32,
-                    Resources {
-                        ore: 0,
-                        clay: 0,
-                        obsidian: 0,
-                        geode: 0,
-                    },
-                )
-            })
-            .product::<usize>()
-    };
+    let result2 = blueprints
+        .iter()
+        .take(3)
+        .map(|blueprint| most_geodes(blueprint, 32))
+        .product::<usize>();
 
     (result1.into(), result2.into())
 }
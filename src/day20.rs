@@ -1,53 +1,42 @@
 #[allow(unused)]
 use crate::prelude::*;
+use crate::sequence::Sequence;
 
-pub fn run(input: &str) -> (Solution, Solution) {
-    let mut input = input
-        .lines()
-        .map(|line| line.parse::<isize>().unwrap() * 811589153)
-        .enumerate()
-        .collect_vec();
-
-    let result1 = {
-        for _ in 0..10 {
-            for i in 0..input.len() {
-                let current_index = input
-                    .iter()
-                    .enumerate()
-                    .find(|(_, (original_index, _))| *original_index == i)
-                    .unwrap()
-                    .0;
+const DECRYPTION_KEY: isize = 811589153;
 
-                let shift = input[current_index].1;
-                let target_index =
-                    (current_index as isize + shift).rem_euclid(input.len() as isize - 1) as usize;
+/// Mix the numbers `rounds` times, then add up the 1000th, 2000th and 3000th numbers after 0
+fn grove_coordinates(numbers: &[isize], rounds: usize) -> isize {
+    // Every number is moved in its original order, which is the order of the handles
+    let mut sequence: Sequence<isize> = numbers.iter().copied().collect();
+    for _ in 0..rounds {
+        for (handle, &number) in numbers.iter().enumerate() {
+            sequence.move_by(handle, number);
+        }
+    }
 
-                let start = std::cmp::min(current_index, target_index);
-                let end = std::cmp::max(current_index, target_index);
+    let zero = numbers.iter().position(|&number| number == 0).unwrap();
+    let zero_index = sequence.index_of(zero);
 
-                if current_index == start {
-                    input[start..=end].rotate_left(1);
-                } else {
-                    input[start..=end].rotate_right(1);
-                }
-            }
-        }
+    [1000, 2000, 3000]
+        .into_iter()
+        .map(|offset| sequence.get((zero_index + offset) % numbers.len()).unwrap())
+        .sum()
+}
 
-        let zero_pos = input
-            .iter()
-            .enumerate()
-            .find(|(_, (_, value))| *value == 0)
-            .unwrap()
-            .0;
+pub fn run(input: &str) -> (Solution, Solution) {
+    let numbers = input
+        .lines()
+        .map(|line| line.parse::<isize>().unwrap())
+        .collect_vec();
 
-        input[(zero_pos + 1000) % input.len()].1
-            + input[(zero_pos + 2000) % input.len()].1
-            + input[(zero_pos + 3000) % input.len()].1
-    } as usize;
+    let result1 = grove_coordinates(&numbers, 1) as usize;
 
     let result2 = {
-        // Part 2
-        0
+        let numbers = numbers
+            .iter()
+            .map(|number| number * DECRYPTION_KEY)
+            .collect_vec();
+        grove_coordinates(&numbers, 10) as usize
     };
 
     (result1.into(), result2.into())
@@ -1,5 +1,3 @@
-use std::ops::{Add, Div, Mul, Sub};
-
 use nom::{
     branch::alt,
     bytes::complete::tag,
@@ -9,165 +7,17 @@ use nom::{
     IResult,
 };
 
+use crate::expr::{ExprGraph, Node, Op};
+use crate::parse;
 #[allow(unused)]
 use crate::prelude::*;
 
-#[derive(Clone, Copy)]
-enum Op {
-    Add,
-    Mul,
-    Div,
-    Sub,
-}
-
-impl Op {
-    fn func(self) -> fn(isize, isize) -> isize {
-        match self {
-            Op::Add => Add::add,
-            Op::Mul => Mul::mul,
-            Op::Div => Div::div,
-            Op::Sub => Sub::sub,
-        }
-    }
-}
-
 #[derive(Clone)]
 enum Monkey<'a> {
     Number(isize),
     BinOp(Op, &'a str, &'a str),
 }
 
-struct MonkeyAnnotated<'a> {
-    name: &'a str,
-    monkey: Monkey<'a>,
-    contains_human: bool,
-}
-
-impl<'a> Monkey<'a> {
-    fn eval(&self, monkeys: &HashMap<&str, Self>) -> isize {
-        match self {
-            Monkey::Number(num) => *num,
-            Monkey::BinOp(op, l, r) => {
-                let l = monkeys.get(l).unwrap().eval(monkeys);
-                let r = monkeys.get(r).unwrap().eval(monkeys);
-                op.func()(l, r)
-            }
-        }
-    }
-}
-
-impl<'a> MonkeyAnnotated<'a> {
-    fn solve_human(
-        &self,
-        target: isize,
-        monkeys: &HashMap<&str, Monkey<'a>>,
-        annotated_monkeys: &HashMap<&str, Self>,
-    ) -> isize {
-        match self.monkey {
-            Monkey::Number(_) => target,
-            Monkey::BinOp(op, l, r) => {
-                let left_annotated = annotated_monkeys.get(l).unwrap();
-                let right_annotated = annotated_monkeys.get(r).unwrap();
-
-                match (op, left_annotated, right_annotated) {
-                    (
-                        Op::Add,
-                        nonhuman @ MonkeyAnnotated {
-                            contains_human: false,
-                            ..
-                        },
-                        human,
-                    )
-                    | (
-                        Op::Add,
-                        human @ MonkeyAnnotated {
-                            contains_human: true,
-                            ..
-                        },
-                        nonhuman,
-                    ) => {
-                        let nonhuman_monkey = monkeys.get(nonhuman.name).unwrap();
-                        let new_target = target - nonhuman_monkey.eval(monkeys);
-                        human.solve_human(new_target, monkeys, annotated_monkeys)
-                    }
-                    (
-                        Op::Mul,
-                        nonhuman @ MonkeyAnnotated {
-                            contains_human: false,
-                            ..
-                        },
-                        human,
-                    )
-                    | (
-                        Op::Mul,
-                        human @ MonkeyAnnotated {
-                            contains_human: true,
-                            ..
-                        },
-                        nonhuman,
-                    ) => {
-                        let nonhuman_monkey = monkeys.get(nonhuman.name).unwrap();
-                        let new_target = target / nonhuman_monkey.eval(monkeys);
-                        human.solve_human(new_target, monkeys, annotated_monkeys)
-                    }
-                    (
-                        Op::Div,
-                        nonhuman @ MonkeyAnnotated {
-                            contains_human: false,
-                            ..
-                        },
-                        human,
-                    ) => {
-                        let nonhuman_monkey = monkeys.get(nonhuman.name).unwrap();
-                        let new_target = nonhuman_monkey.eval(monkeys) / target;
-                        human.solve_human(new_target, monkeys, annotated_monkeys)
-                    }
-                    (
-                        Op::Div,
-                        human @ MonkeyAnnotated {
-                            contains_human: true,
-                            ..
-                        },
-                        nonhuman,
-                    ) => {
-                        let nonhuman_monkey = monkeys.get(nonhuman.name).unwrap();
-                        let new_target = target * nonhuman_monkey.eval(monkeys);
-                        human.solve_human(new_target, monkeys, annotated_monkeys)
-                    }
-                    (
-                        Op::Sub,
-                        nonhuman @ MonkeyAnnotated {
-                            contains_human: false,
-                            ..
-                        },
-                        human,
-                    ) => {
-                        let nonhuman_monkey = monkeys.get(nonhuman.name).unwrap();
-                        let new_target = nonhuman_monkey.eval(monkeys) - target;
-                        human.solve_human(new_target, monkeys, annotated_monkeys)
-                    }
-                    (
-                        Op::Sub,
-                        human @ MonkeyAnnotated {
-                            contains_human: true,
-                            ..
-                        },
-                        nonhuman,
-                    ) => {
-                        let nonhuman_monkey = monkeys.get(nonhuman.name).unwrap();
-                        let new_target = target + nonhuman_monkey.eval(monkeys);
-                        human.solve_human(new_target, monkeys, annotated_monkeys)
-                    }
-                }
-            }
-        }
-    }
-}
-
-fn parse_isize(input: &str) -> IResult<&str, isize> {
-    map(nom::character::complete::i64, |n| n as isize)(input)
-}
-
 fn parse_op(input: &str) -> IResult<&str, Op> {
     delimited(
         tag(" "),
@@ -194,73 +44,43 @@ fn parse_line(input: &str) -> IResult<&str, (&str, Monkey<'_>)> {
     separated_pair(alpha1, tag(": "), parse_monkey)(input)
 }
 
-fn build_annotations<'a>(
-    monkey_name: &'a str,
-    monkeys: &HashMap<&'a str, Monkey<'a>>,
-    annotated_monkeys: &mut HashMap<&'a str, MonkeyAnnotated<'a>>,
-) -> bool {
-    let monkey = monkeys.get(monkey_name).unwrap().clone();
-    if monkey_name == "humn" {
-        annotated_monkeys.insert(
-            monkey_name,
-            MonkeyAnnotated {
-                name: monkey_name,
-                monkey,
-                contains_human: true,
-            },
-        );
-        return true;
-    }
-
-    let contains_human = match monkey {
-        Monkey::Number(_) => false,
-        Monkey::BinOp(_, l, r) => {
-            let left_contains_human = build_annotations(l, monkeys, annotated_monkeys);
-            let right_contains_human = build_annotations(r, monkeys, annotated_monkeys);
-
-            left_contains_human || right_contains_human
-        }
-    };
-
-    annotated_monkeys.insert(
-        monkey_name,
-        MonkeyAnnotated {
-            name: monkey_name,
-            monkey,
-            contains_human,
-        },
-    );
-
-    contains_human
-}
-
 pub fn run(input: &str) -> (Solution, Solution) {
-    let monkeys: HashMap<&str, Monkey<'_>> = input
+    let monkeys = input
         .lines()
-        .map(|line| parse_line(line).unwrap().1)
+        .map(|line| parse::finish(parse_line(line)).unwrap_or_else(|error| error.report(input)))
+        .collect_vec();
+
+    let ids: HashMap<&str, usize> = monkeys
+        .iter()
+        .enumerate()
+        .map(|(id, &(name, _))| (name, id))
         .collect();
 
-    let result1 = { monkeys.get("root").unwrap().eval(&monkeys) } as usize;
+    let mut graph = ExprGraph::new();
+    for (_, monkey) in monkeys.iter() {
+        graph.add(match *monkey {
+            Monkey::Number(n) => Node::Constant((n as i128).into()),
+            Monkey::BinOp(op, l, r) => Node::Binary(op, ids[l], ids[r]),
+        });
+    }
 
-    let result2 = {
-        let mut annotated_monkeys = HashMap::default();
-        assert!(build_annotations("root", &monkeys, &mut annotated_monkeys));
+    let (root, humn) = (ids["root"], ids["humn"]);
 
-        let Monkey::BinOp(_, l, r) = monkeys.get("root").unwrap() else {
+    let result1 = {
+        let Node::Constant(shouted) = graph.node(humn) else {
             unreachable!()
         };
+        graph.evaluate(root, shouted).unwrap().to_integer().unwrap() as usize
+    };
 
-        let l_annotated = annotated_monkeys.get(l).unwrap();
-        let r_annotated = annotated_monkeys.get(r).unwrap();
+    let result2 = {
+        let Node::Binary(_, l, r) = graph.node(root) else {
+            unreachable!()
+        };
 
-        if l_annotated.contains_human {
-            let r_monkey = monkeys.get(r).unwrap();
-            l_annotated.solve_human(r_monkey.eval(&monkeys), &monkeys, &annotated_monkeys)
-        } else {
-            let l_monkey = monkeys.get(l).unwrap();
-            r_annotated.solve_human(l_monkey.eval(&monkeys), &monkeys, &annotated_monkeys)
-        }
-    } as usize;
+        graph.set(humn, Node::Unknown);
+        graph.solve_integer(l, r).unwrap() as usize
+    };
 
     (result1.into(), result2.into())
 }
@@ -1,135 +1,98 @@
+use std::time::Duration;
+
+use crate::automaton::{Automaton, RotatingPriority};
 #[allow(unused)]
 use crate::prelude::*;
 
+/// Room left around the elves whenever they reach the edge of the grid
+const MARGIN: usize = 8;
+
+/// One round of the elves spreading out, done for every elf at once
+fn spread_out(elves: &BitGrid, order: impl Iterator<Item = Direction>) -> BitGrid {
+    let mut undecided = elves & &elves.any_neighbour(Direction::ALL);
+
+    let targets = order
+        .map(|direction| {
+            let blocked = elves.any_neighbour([
+                direction.turn_left_45(),
+                direction,
+                direction.turn_right_45(),
+            ]);
+            let proposing = undecided.and_not(&blocked);
+            undecided = undecided.and_not(&proposing);
+
+            (direction, proposing.shifted(direction))
+        })
+        .collect_vec();
+
+    let contested = targets.iter().tuple_combinations().fold(
+        BitGrid::new(elves.width(), elves.height()),
+        |contested, ((_, a), (_, b))| &contested | &(a & b),
+    );
+
+    let mut next_elves = elves.clone();
+    for (direction, target) in targets {
+        let moving = target.and_not(&contested);
+        next_elves = next_elves.and_not(&moving.shifted(direction.reverse()));
+        next_elves |= &moving;
+    }
+
+    next_elves
+}
+
+fn touches_edge(elves: &BitGrid) -> bool {
+    elves
+        .bounding_box()
+        .is_some_and(|((min_x, min_y), (max_x, max_y))| {
+            min_x == 0 || min_y == 0 || max_x == elves.width() || max_y == elves.height()
+        })
+}
+
 pub fn run(input: &str) -> (Solution, Solution) {
     let map: Grid<_> = input.lines().map(|line| line.chars()).collect();
 
-    let elves: HashSet<_> = (0..map.height())
-        .cartesian_product(0..map.width())
-        .filter_map(|(y, x)| (map[(x, y)] == '#').then(|| (x as isize, y as isize)))
-        .collect();
-
-    let mut moves = [
-        |(x, y): (isize, isize)| ((x, y - 1), [(x - 1, y - 1), (x, y - 1), (x + 1, y - 1)]),
-        |(x, y): (isize, isize)| ((x, y + 1), [(x - 1, y + 1), (x, y + 1), (x + 1, y + 1)]),
-        |(x, y): (isize, isize)| ((x - 1, y), [(x - 1, y - 1), (x - 1, y), (x - 1, y + 1)]),
-        |(x, y): (isize, isize)| ((x + 1, y), [(x + 1, y - 1), (x + 1, y), (x + 1, y + 1)]),
-    ]
-    .into_iter()
-    .cycle();
-
-    let result1 = {
-        let mut elves = elves.clone();
-
-        for round in 0.. {
-            // let (min_x, max_x) = elves
-            //     .iter()
-            //     .copied()
-            //     .map(|(x, _)| x)
-            //     .minmax()
-            //     .into_option()
-            //     .unwrap();
-            // let (min_y, max_y) = elves
-            //     .iter()
-            //     .copied()
-            //     .map(|(_, y)| y)
-            //     .minmax()
-            //     .into_option()
-            //     .unwrap();
-
-            // let mut map_2 = grid!['.'; (max_x - min_x + 1) as usize, (max_y - min_y + 1) as usize];
-            // for (x, y) in (min_x..=max_x).cartesian_product(min_y..=max_y) {
-            //     if elves.contains(&(x, y)) {
-            //         map_2[((x - min_x) as usize, (y - min_y) as usize)] = '#';
-            //     }
-            // }
-
-            // println!("{map_2}");
-
-            let mut intended_moves = HashMap::<(isize, isize), Vec<(isize, isize)>>::default();
-
-            'elf_loop: for elf in elves.iter().copied() {
-                let moves = moves.clone().take(4);
-
-                if [-1, 0, 1]
-                    .into_iter()
-                    .cartesian_product([-1, 0, 1])
-                    .filter(|&(x, y)| x != 0 || y != 0)
-                    .any(|(x, y)| elves.contains(&(elf.0 + x, elf.1 + y)))
-                {
-                    for move_attempt in moves {
-                        let (next, seek) = move_attempt(elf);
-
-                        if !seek.iter().any(|seek| elves.contains(&seek)) {
-                            intended_moves.entry(next).or_default().push(elf);
-                            continue 'elf_loop;
-                        }
-                    }
-                }
-
-                assert!(intended_moves.insert(elf, vec![elf]).is_none());
+    let elves = BitGrid::from(&map.map(|&c| c == '#')).pad(MARGIN);
+    // Where the grid's top left corner is relative to the input, as it grows
+    let mut origin = -Point::new(MARGIN, MARGIN).to_signed();
+
+    let priority = RotatingPriority::new([
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ]);
+    let mut recording = Recording::from_env("day23", '.');
+
+    let mut automaton = Automaton::new(
+        elves,
+        |elves: &BitGrid, next_elves: &mut BitGrid, round: usize| {
+            *next_elves = spread_out(elves, priority.order(round).copied());
+
+            // Keep the elves off the edges, so that they always have room to move
+            if touches_edge(next_elves) {
+                *next_elves = next_elves.pad(MARGIN);
+                origin -= Point::new(MARGIN, MARGIN).to_signed();
             }
 
-            let mut next_elves = HashSet::default();
-
-            for (position, moving_elves) in intended_moves {
-                match moving_elves.as_slice() {
-                    [_single_elf] => {
-                        next_elves.insert(position);
-                    }
-                    _ => {
-                        for elf in moving_elves {
-                            next_elves.insert(elf);
-                        }
-                    }
-                }
-            }
+            recording.record_sparse(
+                next_elves
+                    .ones()
+                    .map(|elf| origin + Point::from(elf).to_signed()),
+                '#',
+            );
+        },
+    );
 
-            if elves == next_elves {
-                panic!("Got the answer and it was {round}", round = round + 1);
-            }
-
-            elves = next_elves;
-            moves.next().unwrap();
-        }
-
-        let (min_x, max_x) = elves
-            .iter()
-            .copied()
-            .map(|(x, _)| x)
-            .minmax()
-            .into_option()
-            .unwrap();
-        let (min_y, max_y) = elves
-            .iter()
-            .copied()
-            .map(|(_, y)| y)
-            .minmax()
-            .into_option()
-            .unwrap();
-
-        dbg!(&(min_x, max_x));
-        dbg!(&(min_y, max_y));
-        dbg!(elves.iter().count());
-
-        dbg!(&elves);
-
-        let mut map_2 = grid!['.'; (max_x - min_x + 1) as usize, (max_y - min_y + 1) as usize];
-        for (x, y) in (min_x..=max_x).cartesian_product(min_y..=max_y) {
-            if elves.contains(&(x, y)) {
-                map_2[((x - min_x) as usize, (y - min_y) as usize)] = '#';
-            }
-        }
+    automaton.run_for(10);
 
-        println!("{map_2}");
+    // Count the empty ground in the smallest rectangle containing every elf
+    let result1 = Grid::from(automaton.state())
+        .crop_to(|&elf| elf)
+        .count_where(|&elf| !elf);
 
-        ((max_x - min_x + 1) * (max_y - min_y + 1) - elves.into_iter().count() as isize) as usize
-    };
+    let result2 = automaton.run_to_fixed_point();
 
-    let result2 = {
-        // Part 2
-        0
-    };
+    recording.finish(&CharPalette, 4, Duration::from_millis(50));
 
     (result1.into(), result2.into())
 }
@@ -1,12 +1,14 @@
-use std::fmt::Display;
+use std::{fmt::Display, time::Duration};
 
+use crate::math::{lcm, DynMod};
 #[allow(unused)]
 use crate::prelude::*;
+use crate::search;
 
 #[derive(Clone, PartialEq, Eq)]
 enum Square {
     Wall,
-    Open(Vec<(isize, isize)>),
+    Open(Vec<Direction>),
 }
 
 impl Display for Square {
@@ -15,10 +17,7 @@ impl Display for Square {
             Square::Wall => '#',
             Square::Open(blizzards) => match blizzards.as_slice() {
                 [] => '.',
-                [LEFT] => '<',
-                [RIGHT] => '>',
-                [UP] => '^',
-                [DOWN] => 'v',
+                [blizzard] => blizzard.arrow(),
                 _ => blizzards.len().to_string().chars().take(1).next().unwrap(),
             },
         };
@@ -27,104 +26,106 @@ impl Display for Square {
     }
 }
 
-const LEFT: (isize, isize) = (-1, 0);
-const RIGHT: (isize, isize) = (1, 0);
-const UP: (isize, isize) = (0, -1);
-const DOWN: (isize, isize) = (0, 1);
+fn square_colour(square: &Square) -> Rgb {
+    match square {
+        Square::Wall => [60, 60, 60],
+        Square::Open(blizzards) if blizzards.is_empty() => [240, 240, 240],
+        Square::Open(blizzards) => {
+            let shade = 200 - 40 * blizzards.len().min(4) as u8;
+            [shade, shade, 255]
+        }
+    }
+}
 
 impl From<char> for Square {
     fn from(c: char) -> Self {
         match c {
             '#' => Self::Wall,
             '.' => Self::Open(Vec::new()),
-            '>' => Self::Open(vec![RIGHT]),
-            '<' => Self::Open(vec![LEFT]),
-            '^' => Self::Open(vec![UP]),
-            'v' => Self::Open(vec![DOWN]),
-            _ => unreachable!(),
+            _ => Self::Open(vec![Direction::try_from(c).unwrap()]),
         }
     }
 }
 
+/// The blizzards on `position` after `minute` minutes, worked out from where they would have
+/// started. `map` must wrap around the inside of the walls.
+fn blizzards_at(map: &Grid<Square>, position: (usize, usize), minute: usize) -> Vec<Direction> {
+    if position.1 == 0 || position.1 == map.height() - 1 {
+        // Blizzards never reach the entrance or exit
+        return Vec::new();
+    }
+
+    Direction::ORTHOGONAL
+        .into_iter()
+        .filter(|&direction| {
+            let origin = map
+                .offset(position, direction.vector() * -(minute as isize))
+                .unwrap();
+            matches!(&map[origin], Square::Open(blizzards) if blizzards.contains(&direction))
+        })
+        .collect()
+}
+
 pub fn run(input: &str) -> (Solution, Solution) {
     let map: Grid<Square> = input
         .lines()
-        .map(|line| line.chars().map(|c| Square::from(c)))
+        .map(|line| line.chars().map(Square::from))
         .collect();
 
-    let result1 = {
-        let mut map = map.clone();
-
-        let mut result = 0;
-
-        let start = (1, 0);
-        let end = (map.width() - 2, map.height() - 1);
-
-        for (start, end) in [(start, end), (end, start), (start, end)] {
-            let mut positions = HashSet::from_iter([start]);
-            for num_moves in 1.. {
-                let mut next_map = grid![Square::Open(Vec::new()); map.width(), map.height()];
-
-                for y in 1..(map.height() - 1) {
-                    for x in 1..(map.width() - 1) {
-                        match &map[(x, y)] {
-                            Square::Wall => (),
-                            Square::Open(blizzards) => {
-                                for blizzard in blizzards.iter().copied() {
-                                    let mut next_position = (
-                                        (x as isize + blizzard.0) as usize,
-                                        (y as isize + blizzard.1) as usize,
-                                    );
-
-                                    if next_position.0 == 0 {
-                                        next_position.0 = map.width() - 2;
-                                    } else if next_position.0 == map.width() - 1 {
-                                        next_position.0 = 1;
-                                    } else if next_position.1 == 0 {
-                                        next_position.1 = map.height() - 2;
-                                    } else if next_position.1 == map.height() - 1 {
-                                        next_position.1 = 1;
-                                    }
-
-                                    let Square::Open(next_blizzards) = &mut next_map[next_position] else {
-                                        unreachable!();
-                                    };
-                                    next_blizzards.push(blizzard);
-                                }
-                            }
-                        }
-                    }
-                }
-
-                let mut next_positions = HashSet::default();
+    // Blizzards wrap around the inside of the walls, but the expedition doesn't
+    let blizzard_topology = Topology::Wrapping {
+        min: (1, 1),
+        max: (map.width() - 1, map.height() - 1),
+    };
+    let valley = map.clone().with_topology(blizzard_topology);
+
+    let start = (1, 0);
+    let end = (map.width() - 2, map.height() - 1);
+
+    // The blizzards are back where they started once every one has crossed the valley a whole
+    // number of times
+    let period = lcm(map.width() - 2, map.height() - 2) as u64;
+
+    // Search over (position, minute) pairs, since the blizzards are different every minute, but
+    // only up to the period so that waiting around in the same blizzards isn't searched forever
+    let next_moves = |&(position, minute): &((usize, usize), DynMod)| {
+        let (map, valley) = (&map, &valley);
+        let next_minute = minute + 1;
+        map.neighbours_orthogonal(position)
+            .chain([position])
+            .filter(move |&option| {
+                map[option] != Square::Wall
+                    && blizzards_at(valley, option, next_minute.value() as usize).is_empty()
+            })
+            .map(move |option| (option, next_minute))
+    };
 
-                for position in positions.into_iter() {
-                    for option in map.neighbours_orthogonal(position).chain([position]) {
-                        if map[option] != Square::Wall
-                            && next_map[option] == Square::Open(Vec::new())
-                        {
-                            next_positions.insert(option);
-                        }
-                    }
-                }
+    // There, back to the start, then there again
+    let mut minute = 0;
 
-                positions = next_positions;
+    for (from, to) in [(start, end), (end, start), (start, end)] {
+        let source = (from, DynMod::new(minute as u64, period));
+        let path = search::bfs([source], next_moves, |&(position, _)| position == to)
+            .expect("The blizzards never let the expedition through");
 
-                for y in 1..(map.height() - 1) {
-                    for x in 1..(map.width() - 1) {
-                        map[(x, y)] = next_map[(x, y)].clone();
-                    }
-                }
+        minute += path.cost;
+    }
 
-                if positions.contains(&end) {
-                    result += num_moves;
-                    break;
+    let mut recording = Recording::from_env("day24", Square::Wall);
+    for minute in 0..=minute {
+        recording.record_with(|| {
+            let mut blizzards = map.clone();
+            for (position, square) in blizzards.iter_mut() {
+                if let Square::Open(square) = square {
+                    *square = blizzards_at(&valley, position, minute);
                 }
             }
-        }
+            blizzards
+        });
+    }
+    recording.finish(&square_colour, 8, Duration::from_millis(100));
 
-        result
-    };
+    let result1 = minute;
 
     let result2 = {
         // Part 2
@@ -0,0 +1,475 @@
+//! Arithmetic expressions sharing subexpressions, evaluated exactly over the rationals and solved
+//! for a single unknown.
+
+use std::{
+    fmt,
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+use crate::math::gcd;
+
+/// An exact fraction, always kept in lowest terms with a positive denominator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numerator: i128,
+    denominator: i128,
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational::integer(0);
+
+    pub const fn integer(n: i128) -> Self {
+        Self {
+            numerator: n,
+            denominator: 1,
+        }
+    }
+
+    /// `None` if `denominator` is 0
+    pub fn new(numerator: i128, denominator: i128) -> Option<Self> {
+        if denominator == 0 {
+            return None;
+        }
+
+        let divisor = gcd(numerator, denominator).abs() * denominator.signum();
+        Some(Self {
+            numerator: numerator / divisor,
+            denominator: denominator / divisor,
+        })
+    }
+
+    pub fn to_integer(self) -> Option<i128> {
+        (self.denominator == 1).then_some(self.numerator)
+    }
+
+    /// `None` when dividing by zero
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        Self::new(
+            self.numerator * rhs.denominator,
+            self.denominator * rhs.numerator,
+        )
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(n: i128) -> Self {
+        Self::integer(n)
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.to_integer() {
+            Some(n) => write!(f, "{n}"),
+            None => write!(f, "{}/{}", self.numerator, self.denominator),
+        }
+    }
+}
+
+impl Add for Rational {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(
+            self.numerator * rhs.denominator + rhs.numerator * self.denominator,
+            self.denominator * rhs.denominator,
+        )
+        .unwrap()
+    }
+}
+
+impl Neg for Rational {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            numerator: -self.numerator,
+            ..self
+        }
+    }
+}
+
+impl Sub for Rational {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl Mul for Rational {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(
+            self.numerator * rhs.numerator,
+            self.denominator * rhs.denominator,
+        )
+        .unwrap()
+    }
+}
+
+impl Div for Rational {
+    type Output = Self;
+
+    /// Panics when dividing by zero
+    fn div(self, rhs: Self) -> Self {
+        self.checked_div(rhs).expect("division by zero")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+pub type NodeId = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Node {
+    Constant(Rational),
+    /// The value to solve for. Every `Unknown` node stands for the same value.
+    Unknown,
+    Binary(Op, NodeId, NodeId),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExprError {
+    DivisionByZero,
+    /// The unknown was multiplied by itself, or divided by
+    NonLinear,
+    /// Both sides of an equation change with the unknown at the same rate, so it has either no
+    /// solution or infinitely many
+    NoUniqueSolution,
+    /// The only solution isn't a whole number
+    NotInteger(Rational),
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExprError::DivisionByZero => write!(f, "division by zero"),
+            ExprError::NonLinear => write!(f, "expression isn't linear in the unknown"),
+            ExprError::NoUniqueSolution => write!(f, "equation has no unique solution"),
+            ExprError::NotInteger(solution) => write!(f, "solution {solution} isn't an integer"),
+        }
+    }
+}
+
+/// `slope * x + offset`, for the unknown `x`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Linear {
+    slope: Rational,
+    offset: Rational,
+}
+
+impl Linear {
+    fn constant(offset: Rational) -> Self {
+        Self {
+            slope: Rational::ZERO,
+            offset,
+        }
+    }
+
+    fn is_constant(self) -> bool {
+        self.slope == Rational::ZERO
+    }
+
+    fn scale(self, by: Rational) -> Self {
+        Self {
+            slope: by * self.slope,
+            offset: by * self.offset,
+        }
+    }
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            slope: self.slope + other.slope,
+            offset: self.offset + other.offset,
+        }
+    }
+}
+
+/// `numerator / denominator`, which is what any expression reduces to when the unknown is used
+/// once, or only ever added or scaled. The denominator is 1 whenever it doesn't depend on the
+/// unknown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Fraction {
+    numerator: Linear,
+    denominator: Linear,
+}
+
+impl Fraction {
+    fn linear(numerator: Linear) -> Self {
+        Self {
+            numerator,
+            denominator: Linear::constant(Rational::integer(1)),
+        }
+    }
+
+    fn new(numerator: Linear, denominator: Linear) -> Result<Self, ExprError> {
+        if !denominator.is_constant() {
+            return Ok(Self {
+                numerator,
+                denominator,
+            });
+        }
+
+        let inverse = Rational::integer(1)
+            .checked_div(denominator.offset)
+            .ok_or(ExprError::DivisionByZero)?;
+        Ok(Self::linear(numerator.scale(inverse)))
+    }
+
+    fn as_constant(self) -> Option<Rational> {
+        (self.numerator.is_constant() && self.denominator.is_constant())
+            .then_some(self.numerator.offset)
+    }
+
+    fn as_linear(self) -> Option<Linear> {
+        self.denominator.is_constant().then_some(self.numerator)
+    }
+
+    fn negate(self) -> Self {
+        Self {
+            numerator: self.numerator.scale(Rational::integer(-1)),
+            ..self
+        }
+    }
+
+    fn apply(op: Op, l: Self, r: Self) -> Result<Self, ExprError> {
+        let (l_constant, r_constant) = (l.as_constant(), r.as_constant());
+        let (l_linear, r_linear) = (l.as_linear(), r.as_linear());
+
+        match op {
+            Op::Add => {
+                if let Some(k) = r_constant {
+                    let shift = l.denominator.scale(k);
+                    Self::new(l.numerator.add(shift), l.denominator)
+                } else if l_constant.is_some() {
+                    Self::apply(Op::Add, r, l)
+                } else if let (Some(l), Some(r)) = (l_linear, r_linear) {
+                    Ok(Self::linear(l.add(r)))
+                } else {
+                    Err(ExprError::NonLinear)
+                }
+            }
+            Op::Sub => Self::apply(Op::Add, l, r.negate()),
+            Op::Mul => match (l_constant, r_constant) {
+                (_, Some(k)) => Self::new(l.numerator.scale(k), l.denominator),
+                (Some(k), _) => Self::new(r.numerator.scale(k), r.denominator),
+                _ => Err(ExprError::NonLinear),
+            },
+            Op::Div => {
+                if let Some(k) = r_constant {
+                    Self::new(l.numerator, l.denominator.scale(k))
+                } else if let Some(k) = l_constant {
+                    Self::new(r.denominator.scale(k), r.numerator)
+                } else if let (Some(l), Some(r)) = (l_linear, r_linear) {
+                    Self::new(l, r)
+                } else {
+                    Err(ExprError::NonLinear)
+                }
+            }
+        }
+    }
+
+    /// The value of the unknown that makes the fraction equal to `target`
+    fn solve(self, target: Rational) -> Result<Rational, ExprError> {
+        // n.slope * x + n.offset = target * (d.slope * x + d.offset)
+        let (n, d) = (self.numerator, self.denominator);
+        let solution = (target * d.offset - n.offset)
+            .checked_div(n.slope - target * d.slope)
+            .ok_or(ExprError::NoUniqueSolution)?;
+
+        if d.slope * solution + d.offset == Rational::ZERO {
+            return Err(ExprError::DivisionByZero);
+        }
+        Ok(solution)
+    }
+}
+
+/// A set of expressions stored as a DAG, so that a subexpression used in several places is only
+/// stored and evaluated once
+#[derive(Debug, Clone, Default)]
+pub struct ExprGraph {
+    nodes: Vec<Node>,
+}
+
+impl ExprGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, node: Node) -> NodeId {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    pub fn set(&mut self, id: NodeId, node: Node) {
+        self.nodes[id] = node;
+    }
+
+    pub fn node(&self, id: NodeId) -> Node {
+        self.nodes[id]
+    }
+
+    /// Combine the values of the nodes below `root`, bottom up, visiting each node once
+    fn fold<V: Copy>(
+        &self,
+        root: NodeId,
+        mut leaf: impl FnMut(Node) -> Result<V, ExprError>,
+        mut combine: impl FnMut(Op, V, V) -> Result<V, ExprError>,
+    ) -> Result<V, ExprError> {
+        let mut values: Vec<Option<V>> = vec![None; self.nodes.len()];
+
+        // Each node is pushed once to have its children pushed, then again once they're done
+        let mut stack = vec![(root, false)];
+        while let Some((id, children_done)) = stack.pop() {
+            if values[id].is_some() {
+                continue;
+            }
+
+            values[id] = match self.nodes[id] {
+                Node::Binary(op, l, r) if children_done => {
+                    Some(combine(op, values[l].unwrap(), values[r].unwrap())?)
+                }
+                Node::Binary(_, l, r) => {
+                    stack.extend([(id, true), (l, false), (r, false)]);
+                    None
+                }
+                node => Some(leaf(node)?),
+            };
+        }
+
+        Ok(values[root].unwrap())
+    }
+
+    /// The value of `root`, with every `Unknown` standing for `unknown`
+    pub fn evaluate(&self, root: NodeId, unknown: Rational) -> Result<Rational, ExprError> {
+        self.fold(
+            root,
+            |node| match node {
+                Node::Constant(value) => Ok(value),
+                _ => Ok(unknown),
+            },
+            |op, l, r| match op {
+                Op::Add => Ok(l + r),
+                Op::Sub => Ok(l - r),
+                Op::Mul => Ok(l * r),
+                Op::Div => l.checked_div(r).ok_or(ExprError::DivisionByZero),
+            },
+        )
+    }
+
+    /// `root` as a fraction in terms of the unknown
+    fn fraction(&self, root: NodeId) -> Result<Fraction, ExprError> {
+        self.fold(
+            root,
+            |node| match node {
+                Node::Constant(value) => Ok(Fraction::linear(Linear::constant(value))),
+                _ => Ok(Fraction::linear(Linear {
+                    slope: Rational::integer(1),
+                    offset: Rational::ZERO,
+                })),
+            },
+            Fraction::apply,
+        )
+    }
+
+    /// The value of the unknown that makes `left` and `right` equal. The unknown can be on either
+    /// side of any operator, as long as it's only used once, or only ever added or scaled.
+    pub fn solve(&self, left: NodeId, right: NodeId) -> Result<Rational, ExprError> {
+        let (left, right) = (self.fraction(left)?, self.fraction(right)?);
+
+        if let Some(target) = right.as_constant() {
+            left.solve(target)
+        } else if let Some(target) = left.as_constant() {
+            right.solve(target)
+        } else {
+            Fraction::apply(Op::Sub, left, right)?.solve(Rational::ZERO)
+        }
+    }
+
+    /// Like [`solve`](Self::solve), for when only whole numbers make sense
+    pub fn solve_integer(&self, left: NodeId, right: NodeId) -> Result<i128, ExprError> {
+        let solution = self.solve(left, right)?;
+        solution.to_integer().ok_or(ExprError::NotInteger(solution))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn constant(graph: &mut ExprGraph, n: i128) -> NodeId {
+        graph.add(Node::Constant(n.into()))
+    }
+
+    #[test]
+    fn rationals() {
+        let minus_half = Rational::new(2, -4).unwrap();
+        assert_eq!(minus_half.to_string(), "-1/2");
+        assert_eq!((minus_half + minus_half).to_integer(), Some(-1));
+        assert_eq!(Rational::new(1, 0), None);
+        assert_eq!(
+            Rational::integer(3) / Rational::integer(6) * minus_half,
+            -(minus_half * minus_half)
+        );
+    }
+
+    #[test]
+    fn unknown_on_either_side() {
+        let mut graph = ExprGraph::new();
+        let x = graph.add(Node::Unknown);
+        let hundred = constant(&mut graph, 100);
+        let two = constant(&mut graph, 2);
+        let quotient = graph.add(Node::Binary(Op::Div, hundred, x));
+        assert_eq!(
+            graph.evaluate(quotient, 8.into()).unwrap().to_string(),
+            "25/2"
+        );
+
+        let target = constant(&mut graph, 5);
+        assert_eq!(graph.solve_integer(quotient, target), Ok(20));
+        let inverse = graph.add(Node::Binary(Op::Div, two, quotient));
+        assert_eq!(graph.solve_integer(target, inverse), Ok(250));
+
+        let squared = graph.add(Node::Binary(Op::Mul, x, x));
+        assert_eq!(graph.solve(squared, target), Err(ExprError::NonLinear));
+
+        // 2 * (x - 4) + x is linear even though x is used twice
+        let four = constant(&mut graph, 4);
+        let difference = graph.add(Node::Binary(Op::Sub, x, four));
+        let doubled = graph.add(Node::Binary(Op::Mul, two, difference));
+        let sum = graph.add(Node::Binary(Op::Add, doubled, x));
+        assert_eq!(
+            graph.solve_integer(target, sum),
+            Err(ExprError::NotInteger(Rational::new(13, 3).unwrap()))
+        );
+
+        let thirteen = constant(&mut graph, 13);
+        assert_eq!(graph.solve_integer(sum, thirteen), Ok(7));
+    }
+
+    #[test]
+    fn degenerate_equations() {
+        let mut graph = ExprGraph::new();
+        let x = graph.add(Node::Unknown);
+        let zero = constant(&mut graph, 0);
+        let one = constant(&mut graph, 1);
+
+        let plus_one = graph.add(Node::Binary(Op::Add, x, one));
+        assert_eq!(graph.solve(x, plus_one), Err(ExprError::NoUniqueSolution));
+
+        let by_zero = graph.add(Node::Binary(Op::Div, x, zero));
+        assert_eq!(graph.solve(by_zero, one), Err(ExprError::DivisionByZero));
+        assert_eq!(
+            graph.evaluate(by_zero, 3.into()),
+            Err(ExprError::DivisionByZero)
+        );
+    }
+}
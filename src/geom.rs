@@ -0,0 +1,312 @@
+use std::{
+    fmt::Display,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
+
+/// A position or vector on the plane. `y` grows downwards, like the rows of a [`Grid`](crate::grid::Grid).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+/// A displacement between two points
+pub type Vector = Point<isize>;
+
+impl<T> Point<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T> From<(T, T)> for Point<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T> From<Point<T>> for (T, T) {
+    fn from(point: Point<T>) -> Self {
+        (point.x, point.y)
+    }
+}
+
+impl<T: Display> Display for Point<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl<T: Add<Output = T>> Add for Point<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl<T: AddAssign> AddAssign for Point<T> {
+    fn add_assign(&mut self, rhs: Self) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+    }
+}
+
+impl<T: SubAssign> SubAssign for Point<T> {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.x -= rhs.x;
+        self.y -= rhs.y;
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Point<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl<T: Mul<Output = T> + Copy> Mul<T> for Point<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Self::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl Add<Direction> for Point<isize> {
+    type Output = Self;
+
+    fn add(self, rhs: Direction) -> Self::Output {
+        self + rhs.vector()
+    }
+}
+
+impl AddAssign<Direction> for Point<isize> {
+    fn add_assign(&mut self, rhs: Direction) {
+        *self += rhs.vector();
+    }
+}
+
+impl Point<isize> {
+    pub fn manhattan(self, other: Self) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    pub fn chebyshev(self, other: Self) -> usize {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+
+    /// Componentwise signum, i.e. the vector of length at most 1 on each axis pointing the same way
+    pub fn signum(self) -> Self {
+        Self::new(self.x.signum(), self.y.signum())
+    }
+
+    /// Both coordinates as `usize`, if neither is negative
+    pub fn to_unsigned(self) -> Option<Point<usize>> {
+        Some(Point::new(
+            usize::try_from(self.x).ok()?,
+            usize::try_from(self.y).ok()?,
+        ))
+    }
+}
+
+impl Point<usize> {
+    pub fn manhattan(self, other: Self) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    pub fn to_signed(self) -> Point<isize> {
+        Point::new(self.x as isize, self.y as isize)
+    }
+
+    /// Shift by a signed vector, or `None` if either coordinate would become negative
+    pub fn checked_add_signed(self, vector: Vector) -> Option<Self> {
+        Some(Self::new(
+            self.x.checked_add_signed(vector.x)?,
+            self.y.checked_add_signed(vector.y)?,
+        ))
+    }
+}
+
+/// One of the 8 compass directions, in clockwise order starting from `Right`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+    Up,
+    UpRight,
+}
+
+impl Direction {
+    /// Right, down, left and up, in the order of their facing scores
+    pub const ORTHOGONAL: [Self; 4] = [Self::Right, Self::Down, Self::Left, Self::Up];
+
+    /// All 8 directions, clockwise starting from `Right`
+    pub const ALL: [Self; 8] = [
+        Self::Right,
+        Self::DownRight,
+        Self::Down,
+        Self::DownLeft,
+        Self::Left,
+        Self::UpLeft,
+        Self::Up,
+        Self::UpRight,
+    ];
+
+    fn from_index(index: usize) -> Self {
+        Self::ALL[index % 8]
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn vector(self) -> Vector {
+        let (x, y) = match self {
+            Self::Right => (1, 0),
+            Self::DownRight => (1, 1),
+            Self::Down => (0, 1),
+            Self::DownLeft => (-1, 1),
+            Self::Left => (-1, 0),
+            Self::UpLeft => (-1, -1),
+            Self::Up => (0, -1),
+            Self::UpRight => (1, -1),
+        };
+
+        Point::new(x, y)
+    }
+
+    pub fn is_orthogonal(self) -> bool {
+        self.index() & 1 == 0
+    }
+
+    /// Quarter turn counterclockwise
+    pub fn turn_left(self) -> Self {
+        Self::from_index(self.index() + 6)
+    }
+
+    /// Quarter turn clockwise
+    pub fn turn_right(self) -> Self {
+        Self::from_index(self.index() + 2)
+    }
+
+    /// Eighth of a turn counterclockwise
+    pub fn turn_left_45(self) -> Self {
+        Self::from_index(self.index() + 7)
+    }
+
+    /// Eighth of a turn clockwise
+    pub fn turn_right_45(self) -> Self {
+        Self::from_index(self.index() + 1)
+    }
+
+    pub fn reverse(self) -> Self {
+        Self::from_index(self.index() + 4)
+    }
+
+    /// 0 for right, 1 for down, 2 for left and 3 for up
+    pub fn facing(self) -> usize {
+        assert!(
+            self.is_orthogonal(),
+            "Only orthogonal directions have a facing"
+        );
+        self.index() / 2
+    }
+
+    pub fn from_facing(facing: usize) -> Self {
+        Self::ORTHOGONAL[facing % 4]
+    }
+
+    /// The arrow used to draw an orthogonal direction on a map
+    pub fn arrow(self) -> char {
+        match self {
+            Self::Right => '>',
+            Self::Down => 'v',
+            Self::Left => '<',
+            Self::Up => '^',
+            _ => panic!("Only orthogonal directions have an arrow"),
+        }
+    }
+}
+
+impl From<Direction> for (isize, isize) {
+    fn from(direction: Direction) -> Self {
+        direction.vector().into()
+    }
+}
+
+impl TryFrom<char> for Direction {
+    type Error = char;
+
+    /// Accepts both arrows (`>v<^`) and letters (`RDLU`)
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        Ok(match c {
+            '>' | 'R' => Self::Right,
+            'v' | 'D' => Self::Down,
+            '<' | 'L' => Self::Left,
+            '^' | 'U' => Self::Up,
+            _ => return Err(c),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turning() {
+        for direction in Direction::ALL {
+            assert_eq!(direction.turn_left().turn_right(), direction);
+            assert_eq!(direction.turn_right().turn_right(), direction.reverse());
+            assert_eq!(
+                direction.turn_left_45().turn_left_45(),
+                direction.turn_left()
+            );
+            assert_eq!(direction.reverse().vector(), -direction.vector());
+        }
+
+        assert_eq!(Direction::Right.turn_right(), Direction::Down);
+        assert_eq!(Direction::Up.turn_left(), Direction::Left);
+        assert_eq!(Direction::Up.turn_right_45(), Direction::UpRight);
+        assert_eq!(Direction::Up.facing(), 3);
+        assert_eq!(Direction::from_facing(1), Direction::Down);
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = Point::new(3isize, -2);
+        let b = Point::new(-1isize, 4);
+
+        assert_eq!(a + b, Point::new(2, 2));
+        assert_eq!(a - b, Point::new(4, -6));
+        assert_eq!(a * 2, Point::new(6, -4));
+        assert_eq!(a + Direction::Up, Point::new(3, -3));
+        assert_eq!(a.manhattan(b), 10);
+        assert_eq!(a.chebyshev(b), 6);
+        assert_eq!((a - b).signum(), Point::new(1, -1));
+        assert_eq!(a.to_unsigned(), None);
+        assert_eq!(
+            Point::new(1usize, 0).checked_add_signed(Direction::Left.vector()),
+            Some(Point::new(0, 0))
+        );
+        assert_eq!(
+            Point::new(1usize, 0).checked_add_signed(Direction::Up.vector()),
+            None
+        );
+    }
+}
@@ -0,0 +1,384 @@
+use std::{
+    fmt::Display,
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, Not},
+};
+
+use itertools::Itertools;
+
+use crate::geom::Direction;
+
+use super::{Grid, GridPosition};
+
+const WORD: usize = u64::BITS as usize;
+
+/// A grid of booleans packed 64 to a word, one run of words per row. Bit `x % 64` of word `x / 64`
+/// holds column `x`, and the unused high bits of each row's last word are always clear.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        let words_per_row = width.div_ceil(WORD);
+
+        Self {
+            width,
+            height,
+            words_per_row,
+            words: vec![0; words_per_row * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Add empty rows to the bottom of the grid, or drop rows from the bottom, to make it `height`
+    /// rows tall
+    pub fn resize_rows(&mut self, height: usize) {
+        self.words.resize(self.words_per_row * height, 0);
+        self.height = height;
+    }
+
+    /// Mask of the bits in use in the last word of a row
+    fn last_word_mask(&self) -> u64 {
+        match self.width % WORD {
+            0 => u64::MAX,
+            used => (1 << used) - 1,
+        }
+    }
+
+    fn clear_unused_bits(&mut self) {
+        let mask = self.last_word_mask();
+        for row in self.words.chunks_mut(self.words_per_row) {
+            if let Some(last) = row.last_mut() {
+                *last &= mask;
+            }
+        }
+    }
+
+    fn locate(&self, position: impl GridPosition) -> Option<(usize, u64)> {
+        let (x, y) = position.to_grid_position()?;
+        if x < self.width && y < self.height {
+            Some((y * self.words_per_row + x / WORD, 1 << (x % WORD)))
+        } else {
+            None
+        }
+    }
+
+    /// Whether the square at `position` is set. Squares outside the grid never are.
+    pub fn get(&self, position: impl GridPosition) -> bool {
+        self.locate(position)
+            .is_some_and(|(word, bit)| self.words[word] & bit != 0)
+    }
+
+    pub fn set(&mut self, position: (usize, usize), value: bool) {
+        let (word, bit) = self.locate(position).expect("Position is outside the grid");
+
+        if value {
+            self.words[word] |= bit;
+        } else {
+            self.words[word] &= !bit;
+        }
+    }
+
+    pub fn row(&self, y: usize) -> &[u64] {
+        &self.words[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn row_count_ones(&self, y: usize) -> usize {
+        self.row(y)
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Positions of the set squares, in row-major order
+    pub fn ones(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.words
+            .iter()
+            .enumerate()
+            .flat_map(move |(index, &word)| {
+                let (y, x_base) = (
+                    index / self.words_per_row,
+                    index % self.words_per_row * WORD,
+                );
+
+                std::iter::successors(Some(word).filter(|&word| word != 0), |&word| {
+                    Some(word & (word - 1)).filter(|&word| word != 0)
+                })
+                .map(move |word| (x_base + word.trailing_zeros() as usize, y))
+            })
+    }
+
+    /// The half-open `(min, max)` box around every set square, or `None` if there aren't any
+    pub fn bounding_box(&self) -> Option<((usize, usize), (usize, usize))> {
+        let (min_y, max_y) = (0..self.height)
+            .filter(|&y| self.row(y).iter().any(|&word| word != 0))
+            .minmax()
+            .into_option()?;
+
+        let columns = (0..self.words_per_row)
+            .map(|i| (0..self.height).fold(0, |acc, y| acc | self.row(y)[i]))
+            .collect_vec();
+        let min_x = columns
+            .iter()
+            .position(|&word| word != 0)
+            .map(|i| i * WORD + columns[i].trailing_zeros() as usize)?;
+        let max_x = columns
+            .iter()
+            .rposition(|&word| word != 0)
+            .map(|i| i * WORD + (WORD - columns[i].leading_zeros() as usize))?;
+
+        Some(((min_x, min_y), (max_x, max_y + 1)))
+    }
+
+    /// The same squares with `n` unset squares added around every edge
+    pub fn pad(&self, n: usize) -> Self {
+        let mut padded = Self::new(self.width + 2 * n, self.height + 2 * n);
+        let mask = padded.last_word_mask();
+        for y in 0..self.height {
+            shift_row(self.row(y), padded.row_mut(y + n), n as isize, mask);
+        }
+
+        padded
+    }
+
+    fn row_mut(&mut self, y: usize) -> &mut [u64] {
+        &mut self.words[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+
+    /// Every square moved by `delta`. Squares pushed past an edge are lost, and the ones uncovered
+    /// are unset.
+    pub fn shifted(&self, delta: impl Into<(isize, isize)>) -> Self {
+        let (dx, dy) = delta.into();
+        let mut shifted = Self::new(self.width, self.height);
+        let mask = self.last_word_mask();
+
+        for y in 0..self.height {
+            let Some(source) = y
+                .checked_add_signed(-dy)
+                .filter(|&source| source < self.height)
+            else {
+                continue;
+            };
+            shift_row(self.row(source), shifted.row_mut(y), dx, mask);
+        }
+
+        shifted
+    }
+
+    /// Squares with at least one set square in any of `directions` from them
+    pub fn any_neighbour(&self, directions: impl IntoIterator<Item = Direction>) -> Self {
+        directions
+            .into_iter()
+            .fold(Self::new(self.width, self.height), |mut mask, direction| {
+                mask |= &self.shifted(direction.reverse());
+                mask
+            })
+    }
+
+    pub fn and_not(&self, other: &Self) -> Self {
+        self.zip_words(other, |a, b| a & !b)
+    }
+
+    fn zip_words(&self, other: &Self, f: impl Fn(u64, u64) -> u64) -> Self {
+        assert_eq!(
+            (self.width, self.height),
+            (other.width, other.height),
+            "Combined grids must have the same size"
+        );
+
+        Self {
+            words: self
+                .words
+                .iter()
+                .zip(&other.words)
+                .map(|(&a, &b)| f(a, b))
+                .collect(),
+            ..*self
+        }
+    }
+}
+
+/// Write `source` moved `dx` columns to the right into `dest`, masking the last word with `mask`
+fn shift_row(source: &[u64], dest: &mut [u64], dx: isize, mask: u64) {
+    let word_at = |index: isize| {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| source.get(index).copied())
+            .unwrap_or(0)
+    };
+
+    for (i, word) in dest.iter_mut().enumerate() {
+        // The first source column landing in this word
+        let start = (i * WORD) as isize - dx;
+        let (index, bit) = (
+            start.div_euclid(WORD as isize),
+            start.rem_euclid(WORD as isize),
+        );
+
+        *word = if bit == 0 {
+            word_at(index)
+        } else {
+            (word_at(index) >> bit) | (word_at(index + 1) << (WORD as isize - bit))
+        };
+    }
+
+    if let Some(last) = dest.last_mut() {
+        *last &= mask;
+    }
+}
+
+impl BitAnd for &BitGrid {
+    type Output = BitGrid;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.zip_words(rhs, |a, b| a & b)
+    }
+}
+
+impl BitOr for &BitGrid {
+    type Output = BitGrid;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.zip_words(rhs, |a, b| a | b)
+    }
+}
+
+impl BitXor for &BitGrid {
+    type Output = BitGrid;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        self.zip_words(rhs, |a, b| a ^ b)
+    }
+}
+
+impl Not for &BitGrid {
+    type Output = BitGrid;
+
+    fn not(self) -> Self::Output {
+        let mut inverted = BitGrid {
+            words: self.words.iter().map(|word| !word).collect(),
+            ..*self
+        };
+        inverted.clear_unused_bits();
+        inverted
+    }
+}
+
+impl BitAndAssign<&BitGrid> for BitGrid {
+    fn bitand_assign(&mut self, rhs: &BitGrid) {
+        *self = &*self & rhs;
+    }
+}
+
+impl BitOrAssign<&BitGrid> for BitGrid {
+    fn bitor_assign(&mut self, rhs: &BitGrid) {
+        *self = &*self | rhs;
+    }
+}
+
+impl Display for BitGrid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                write!(f, "{}", if self.get((x, y)) { '#' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl From<&Grid<bool>> for BitGrid {
+    fn from(grid: &Grid<bool>) -> Self {
+        let mut bits = Self::new(grid.width(), grid.height());
+        for (position, _) in grid.iter().filter(|(_, &set)| set) {
+            bits.set(position, true);
+        }
+
+        bits
+    }
+}
+
+impl From<&BitGrid> for Grid<bool> {
+    fn from(bits: &BitGrid) -> Self {
+        (0..bits.height())
+            .map(|y| (0..bits.width()).map(move |x| bits.get((x, y))))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bits(rows: &[&str]) -> BitGrid {
+        let grid: Grid<bool> = rows
+            .iter()
+            .map(|row| row.chars().map(|c| c == '#'))
+            .collect();
+        BitGrid::from(&grid)
+    }
+
+    #[test]
+    fn shifts_cross_words() {
+        let mut grid = BitGrid::new(70, 2);
+        grid.set((63, 0), true);
+        grid.set((69, 1), true);
+
+        let right = grid.shifted((1, 0));
+        assert_eq!(right.ones().collect_vec(), [(64, 0)]);
+
+        let left = grid.shifted((-64, 0));
+        assert_eq!(left.ones().collect_vec(), [(5, 1)]);
+
+        assert_eq!((!&grid).count_ones(), 140 - 2);
+        assert_eq!(grid.bounding_box(), Some(((63, 0), (70, 2))));
+
+        let mut padded = grid.pad(1);
+        assert_eq!(padded.ones().collect_vec(), [(64, 1), (70, 2)]);
+
+        padded.resize_rows(2);
+        assert_eq!(padded.ones().collect_vec(), [(64, 1)]);
+        padded.resize_rows(5);
+        assert_eq!((padded.height(), padded.count_ones()), (5, 1));
+    }
+
+    #[test]
+    fn neighbour_masks() {
+        let grid = bits(&["...", ".#.", "..."]);
+
+        assert_eq!(
+            grid.any_neighbour(Direction::ALL).to_string(),
+            "###\n#.#\n###\n"
+        );
+        assert_eq!(
+            grid.any_neighbour([Direction::Up]).to_string(),
+            "...\n...\n.#.\n"
+        );
+
+        let other = bits(&["#..", ".##", "..."]);
+        assert_eq!((&grid & &other).count_ones(), 1);
+        assert_eq!((&grid | &other).row_count_ones(1), 2);
+        assert_eq!(other.and_not(&grid).to_string(), "#..\n..#\n...\n");
+        assert_eq!((&grid ^ &other).count_ones(), 2);
+    }
+}
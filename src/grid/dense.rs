@@ -0,0 +1,307 @@
+use std::{
+    fmt::Display,
+    ops::{Index, IndexMut},
+};
+
+use itertools::Itertools;
+
+use super::{Grid, Topology};
+
+/// A dense grid with any number of dimensions, covering the half-open box `min..max`.
+/// Coordinates are signed so the box doesn't have to start at the origin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DenseGrid<T, const N: usize> {
+    min: [isize; N],
+    size: [usize; N],
+    cells: Vec<T>,
+}
+
+pub type Grid3<T> = DenseGrid<T, 3>;
+
+fn size_between<const N: usize>(min: [isize; N], max: [isize; N]) -> [usize; N] {
+    std::array::from_fn(|i| {
+        assert!(min[i] <= max[i], "Grid bounds are inverted on axis {i}");
+        (max[i] - min[i]) as usize
+    })
+}
+
+impl<T, const N: usize> DenseGrid<T, N>
+where
+    T: Default,
+{
+    pub fn new(min: [isize; N], max: [isize; N]) -> Self {
+        let size = size_between(min, max);
+        let mut cells = Vec::new();
+        cells.resize_with(size.iter().product(), T::default);
+
+        Self { min, size, cells }
+    }
+}
+
+impl<T, const N: usize> DenseGrid<T, N>
+where
+    T: Clone,
+{
+    pub fn new_filled(element: T, min: [isize; N], max: [isize; N]) -> Self {
+        let size = size_between(min, max);
+        let cells = vec![element; size.iter().product()];
+
+        Self { min, size, cells }
+    }
+
+    /// Surround the grid with `n` cells of `fill` on every side, keeping the existing cells where
+    /// they are
+    pub fn pad(&self, n: usize, fill: T) -> Self {
+        let n = n as isize;
+        let max = self.max();
+        let mut padded = Self::new_filled(
+            fill,
+            self.min.map(|min| min - n),
+            std::array::from_fn(|axis| max[axis] + n),
+        );
+
+        for (position, cell) in self.iter() {
+            padded[position] = cell.clone();
+        }
+
+        padded
+    }
+}
+
+impl<T, const N: usize> DenseGrid<T, N> {
+    /// Smallest position in the grid
+    pub fn min(&self) -> [isize; N] {
+        self.min
+    }
+
+    /// One past the largest position in the grid, on every axis
+    pub fn max(&self) -> [isize; N] {
+        std::array::from_fn(|i| self.min[i] + self.size[i] as isize)
+    }
+
+    pub fn size(&self) -> [usize; N] {
+        self.size
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    fn flat_index(&self, position: [isize; N]) -> Option<usize> {
+        let mut index = 0;
+        for axis in (0..N).rev() {
+            let offset = usize::try_from(position[axis] - self.min[axis])
+                .ok()
+                .filter(|&offset| offset < self.size[axis])?;
+            index = index * self.size[axis] + offset;
+        }
+
+        Some(index)
+    }
+
+    fn position_of(&self, mut index: usize) -> [isize; N] {
+        std::array::from_fn(|axis| {
+            let offset = index % self.size[axis];
+            index /= self.size[axis];
+            self.min[axis] + offset as isize
+        })
+    }
+
+    pub fn contains(&self, position: [isize; N]) -> bool {
+        self.flat_index(position).is_some()
+    }
+
+    pub fn get(&self, position: [isize; N]) -> Option<&T> {
+        self.flat_index(position).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, position: [isize; N]) -> Option<&mut T> {
+        self.flat_index(position)
+            .map(|index| &mut self.cells[index])
+    }
+
+    /// Every position in the grid, with the first axis changing fastest
+    pub fn positions(&self) -> impl Iterator<Item = [isize; N]> + '_ {
+        (0..self.len()).map(|index| self.position_of(index))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ([isize; N], &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(index, cell)| (self.position_of(index), cell))
+    }
+
+    pub fn flat_iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.cells.iter_mut()
+    }
+
+    pub fn into_flat_iter(self) -> impl DoubleEndedIterator<Item = T> {
+        self.cells.into_iter()
+    }
+
+    fn in_bounds(min: [isize; N], max: [isize; N]) -> impl Fn(&[isize; N]) -> bool {
+        move |position| (0..N).all(|axis| (min[axis]..max[axis]).contains(&position[axis]))
+    }
+
+    /// The `2 * N` positions sharing a face with `position`
+    pub fn face_neighbours(&self, position: [isize; N]) -> impl Iterator<Item = [isize; N]> {
+        (0..N)
+            .cartesian_product([-1, 1])
+            .map(move |(axis, shift)| {
+                let mut neighbour = position;
+                neighbour[axis] += shift;
+                neighbour
+            })
+            .filter(Self::in_bounds(self.min, self.max()))
+    }
+
+    /// The `3^N - 1` positions touching `position`, even if only by a corner
+    pub fn neighbours(&self, position: [isize; N]) -> impl Iterator<Item = [isize; N]> {
+        (0..3usize.pow(N as u32))
+            .map(move |mut shifts| {
+                let mut neighbour = position;
+                for coordinate in neighbour.iter_mut() {
+                    *coordinate += (shifts % 3) as isize - 1;
+                    shifts /= 3;
+                }
+                neighbour
+            })
+            .filter(move |&neighbour| neighbour != position)
+            .filter(Self::in_bounds(self.min, self.max()))
+    }
+}
+
+impl<T, const N: usize> Index<[isize; N]> for DenseGrid<T, N> {
+    type Output = T;
+
+    fn index(&self, position: [isize; N]) -> &Self::Output {
+        match self.flat_index(position) {
+            Some(index) => &self.cells[index],
+            None => panic!("Position {position:?} is outside the grid"),
+        }
+    }
+}
+
+impl<T, const N: usize> IndexMut<[isize; N]> for DenseGrid<T, N> {
+    fn index_mut(&mut self, position: [isize; N]) -> &mut Self::Output {
+        match self.flat_index(position) {
+            Some(index) => &mut self.cells[index],
+            None => panic!("Position {position:?} is outside the grid"),
+        }
+    }
+}
+
+/// Prints one 2D slice at a time. When there are more than 2 dimensions, each slice is headed by
+/// the coordinates of its remaining axes.
+impl<T: Display, const N: usize> Display for DenseGrid<T, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let row_length = self.size.first().copied().unwrap_or(1).max(1);
+        let slice_height = self.size.get(1).copied().unwrap_or(1).max(1);
+
+        for (i, row) in self.cells.chunks(row_length).enumerate() {
+            if i % slice_height == 0 && N > 2 {
+                if i > 0 {
+                    writeln!(f)?;
+                }
+                let position = self.position_of(i * row_length);
+                writeln!(f, "{:?}", &position[2..])?;
+            }
+
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> From<Grid<T>> for DenseGrid<T, 2> {
+    fn from(grid: Grid<T>) -> Self {
+        Self {
+            min: [0, 0],
+            size: [grid.width, grid.height],
+            cells: grid.grid,
+        }
+    }
+}
+
+/// Drops the origin, so the grid's `min` becomes `(0, 0)`
+impl<T> From<DenseGrid<T, 2>> for Grid<T> {
+    fn from(grid: DenseGrid<T, 2>) -> Self {
+        Self {
+            width: grid.size[0],
+            height: grid.size[1],
+            grid: grid.cells,
+            topology: Topology::Bounded,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offset_origin() {
+        let mut grid = Grid3::new_filled(0u8, [-1, -2, 3], [2, 1, 5]);
+        assert_eq!(grid.len(), 3 * 3 * 2);
+        assert_eq!(grid.max(), [2, 1, 5]);
+
+        grid[[-1, -2, 3]] = 1;
+        grid[[1, 0, 4]] = 2;
+
+        assert_eq!(grid.get([-1, -2, 3]), Some(&1));
+        assert_eq!(grid.get([2, 0, 4]), None);
+        assert_eq!(grid.get([0, 0, 2]), None);
+        assert_eq!(grid.positions().next(), Some([-1, -2, 3]));
+        assert_eq!(grid.positions().last(), Some([1, 0, 4]));
+        assert!(grid
+            .positions()
+            .all(|position| grid.get(position).is_some()));
+    }
+
+    #[test]
+    fn padding() {
+        let grid = Grid3::new_filled(1u8, [0, 0, 0], [1, 2, 1]).pad(1, 0);
+
+        assert_eq!(grid.min(), [-1, -1, -1]);
+        assert_eq!(grid.max(), [2, 3, 2]);
+        assert_eq!(grid[[0, 1, 0]], 1);
+        assert_eq!(grid.iter().filter(|(_, &cell)| cell == 1).count(), 2);
+    }
+
+    #[test]
+    fn neighbourhoods() {
+        let grid = Grid3::new_filled((), [0, 0, 0], [3, 3, 3]);
+
+        assert_eq!(grid.face_neighbours([1, 1, 1]).count(), 6);
+        assert_eq!(grid.face_neighbours([0, 0, 0]).count(), 3);
+        assert_eq!(grid.neighbours([1, 1, 1]).count(), 26);
+        assert_eq!(grid.neighbours([0, 0, 0]).count(), 7);
+    }
+
+    #[test]
+    fn display_slices() {
+        let mut grid = Grid3::new_filled('.', [0, 0, -1], [2, 2, 1]);
+        grid[[1, 0, 0]] = '#';
+
+        assert_eq!(grid.to_string(), "[-1]\n..\n..\n\n[0]\n.#\n..\n");
+    }
+
+    #[test]
+    fn from_grid() {
+        let grid: Grid<char> = ["ab", "cd"].iter().map(|row| row.chars()).collect();
+        let dense = DenseGrid::from(grid);
+
+        assert_eq!(dense[[1, 0]], 'b');
+        assert_eq!(dense.to_string(), "ab\ncd\n");
+        assert_eq!(Grid::from(dense)[(0, 1)], 'c');
+    }
+}
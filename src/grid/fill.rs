@@ -0,0 +1,263 @@
+use std::collections::VecDeque;
+
+use itertools::Either;
+
+use super::{DenseGrid, Grid};
+
+/// Which squares count as touching when filling or labelling a grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighbourhood {
+    /// Only squares sharing an edge (or a face, in more than 2 dimensions)
+    Orthogonal,
+    /// Squares sharing even just a corner
+    Full,
+}
+
+pub type ComponentId = usize;
+
+/// Which component each square of a grid belongs to, if any
+pub type ComponentLabels = Grid<Option<ComponentId>>;
+
+/// A connected group of squares found by `label_components`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Component<P> {
+    pub size: usize,
+    /// Smallest position in the component, on every axis
+    pub min: P,
+    /// One past the largest position in the component, on every axis
+    pub max: P,
+}
+
+impl<T> Grid<T> {
+    fn neighbourhood(
+        &self,
+        position: (usize, usize),
+        neighbourhood: Neighbourhood,
+    ) -> impl Iterator<Item = (usize, usize)> {
+        match neighbourhood {
+            Neighbourhood::Orthogonal => Either::Left(self.neighbours_orthogonal(position)),
+            Neighbourhood::Full => Either::Right(self.neighbours(position)),
+        }
+    }
+
+    /// The number of steps from `start` to every square reachable from it, or `None` for the ones
+    /// that aren't. `passable(from, to)` decides whether a single step can be taken.
+    pub fn flood_fill(
+        &self,
+        start: (usize, usize),
+        neighbourhood: Neighbourhood,
+        mut passable: impl FnMut(&T, &T) -> bool,
+    ) -> Grid<Option<usize>> {
+        let mut distances = crate::grid![None; self.width(), self.height()];
+        distances[start] = Some(0);
+
+        let mut queue = VecDeque::from([start]);
+        while let Some(position) = queue.pop_front() {
+            let distance = distances[position].unwrap();
+            for neighbour in self.neighbourhood(position, neighbourhood) {
+                if distances[neighbour].is_none() && passable(&self[position], &self[neighbour]) {
+                    distances[neighbour] = Some(distance + 1);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+
+        distances
+    }
+
+    /// Split the squares matching `passable` into connected groups. Returns which group each
+    /// square belongs to, and the groups themselves in the order their first square comes up in
+    /// row-major order.
+    pub fn label_components(
+        &self,
+        neighbourhood: Neighbourhood,
+        mut passable: impl FnMut(&T) -> bool,
+    ) -> (ComponentLabels, Vec<Component<(usize, usize)>>) {
+        let mut labels = crate::grid![None; self.width(), self.height()];
+        let mut components = Vec::new();
+
+        for start in self.positions() {
+            if labels[start].is_some() || !passable(&self[start]) {
+                continue;
+            }
+
+            let id = components.len();
+            let mut component = Component {
+                size: 0,
+                min: start,
+                max: (start.0 + 1, start.1 + 1),
+            };
+
+            labels[start] = Some(id);
+            let mut queue = VecDeque::from([start]);
+            while let Some(position @ (x, y)) = queue.pop_front() {
+                component.size += 1;
+                component.min = (component.min.0.min(x), component.min.1.min(y));
+                component.max = (component.max.0.max(x + 1), component.max.1.max(y + 1));
+
+                for neighbour in self.neighbourhood(position, neighbourhood) {
+                    if labels[neighbour].is_none() && passable(&self[neighbour]) {
+                        labels[neighbour] = Some(id);
+                        queue.push_back(neighbour);
+                    }
+                }
+            }
+
+            components.push(component);
+        }
+
+        (labels, components)
+    }
+}
+
+impl<T, const N: usize> DenseGrid<T, N> {
+    fn neighbourhood(
+        &self,
+        position: [isize; N],
+        neighbourhood: Neighbourhood,
+    ) -> impl Iterator<Item = [isize; N]> {
+        match neighbourhood {
+            Neighbourhood::Orthogonal => Either::Left(self.face_neighbours(position)),
+            Neighbourhood::Full => Either::Right(self.neighbours(position)),
+        }
+    }
+
+    /// The number of steps from `start` to every cell reachable from it, or `None` for the ones
+    /// that aren't. `passable(from, to)` decides whether a single step can be taken.
+    pub fn flood_fill(
+        &self,
+        start: [isize; N],
+        neighbourhood: Neighbourhood,
+        mut passable: impl FnMut(&T, &T) -> bool,
+    ) -> DenseGrid<Option<usize>, N> {
+        let mut distances = DenseGrid::new_filled(None, self.min(), self.max());
+        distances[start] = Some(0);
+
+        let mut queue = VecDeque::from([start]);
+        while let Some(position) = queue.pop_front() {
+            let distance = distances[position].unwrap();
+            for neighbour in self.neighbourhood(position, neighbourhood) {
+                if distances[neighbour].is_none() && passable(&self[position], &self[neighbour]) {
+                    distances[neighbour] = Some(distance + 1);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+
+        distances
+    }
+
+    /// Split the cells matching `passable` into connected groups. Returns which group each cell
+    /// belongs to, and the groups themselves in the order their first cell comes up in
+    /// `positions`.
+    pub fn label_components(
+        &self,
+        neighbourhood: Neighbourhood,
+        mut passable: impl FnMut(&T) -> bool,
+    ) -> (
+        DenseGrid<Option<ComponentId>, N>,
+        Vec<Component<[isize; N]>>,
+    ) {
+        let mut labels = DenseGrid::new_filled(None, self.min(), self.max());
+        let mut components = Vec::new();
+
+        for start in self.positions() {
+            if labels[start].is_some() || !passable(&self[start]) {
+                continue;
+            }
+
+            let id = components.len();
+            let mut component = Component {
+                size: 0,
+                min: start,
+                max: start.map(|coordinate| coordinate + 1),
+            };
+
+            labels[start] = Some(id);
+            let mut queue = VecDeque::from([start]);
+            while let Some(position) = queue.pop_front() {
+                component.size += 1;
+                component.min = std::array::from_fn(|axis| component.min[axis].min(position[axis]));
+                component.max =
+                    std::array::from_fn(|axis| component.max[axis].max(position[axis] + 1));
+
+                for neighbour in self.neighbourhood(position, neighbourhood) {
+                    if labels[neighbour].is_none() && passable(&self[neighbour]) {
+                        labels[neighbour] = Some(id);
+                        queue.push_back(neighbour);
+                    }
+                }
+            }
+
+            components.push(component);
+        }
+
+        (labels, components)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid3;
+
+    fn grid(rows: &[&str]) -> Grid<char> {
+        rows.iter().map(|row| row.chars()).collect()
+    }
+
+    #[test]
+    fn flood_distances() {
+        let maze = grid(&["..#", "#.#", "..."]);
+        let distances = maze.flood_fill((0, 0), Neighbourhood::Orthogonal, |_, &to| to == '.');
+
+        assert_eq!(distances[(2, 2)], Some(4));
+        assert_eq!(distances[(2, 0)], None);
+        assert_eq!(distances[(0, 2)], Some(4));
+
+        let diagonal = maze.flood_fill((0, 0), Neighbourhood::Full, |_, &to| to == '.');
+        assert_eq!(diagonal[(2, 2)], Some(2));
+    }
+
+    #[test]
+    fn components() {
+        let map = grid(&["#..#", "#..#", "...#", "###."]);
+
+        let (labels, components) = map.label_components(Neighbourhood::Orthogonal, |&c| c == '#');
+        assert_eq!(components.len(), 3);
+        assert_eq!(
+            components[0],
+            Component {
+                size: 2,
+                min: (0, 0),
+                max: (1, 2)
+            }
+        );
+        assert_eq!(components[1].size, 3);
+        assert_eq!(labels[(3, 2)], Some(1));
+        assert_eq!(labels[(1, 1)], None);
+
+        let (_, components) = map.label_components(Neighbourhood::Full, |&c| c == '#');
+        assert_eq!(components.len(), 2);
+    }
+
+    #[test]
+    fn components_in_3d() {
+        let mut space = Grid3::new_filled(false, [0, 0, 0], [3, 3, 3]);
+        space[[0, 0, 0]] = true;
+        space[[1, 1, 1]] = true;
+        space[[1, 1, 2]] = true;
+
+        let (labels, components) = space.label_components(Neighbourhood::Orthogonal, |&set| set);
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[1].min, [1, 1, 1]);
+        assert_eq!(components[1].max, [2, 2, 3]);
+        assert_eq!(labels[[1, 1, 2]], Some(1));
+
+        let (_, components) = space.label_components(Neighbourhood::Full, |&set| set);
+        assert_eq!(components.len(), 1);
+
+        let distances = space.flood_fill([0, 0, 0], Neighbourhood::Orthogonal, |_, &to| !to);
+        assert_eq!(distances[[2, 2, 2]], Some(6));
+        assert_eq!(distances[[1, 1, 1]], None);
+    }
+}
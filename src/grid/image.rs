@@ -0,0 +1,433 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use super::Grid;
+
+pub type Rgb = [u8; 3];
+
+/// Picks the colour of each square when rendering a grid to an image
+pub trait Colormap<T> {
+    fn colour(&self, value: &T) -> Rgb;
+}
+
+impl<T, F> Colormap<T> for F
+where
+    F: Fn(&T) -> Rgb,
+{
+    fn colour(&self, value: &T) -> Rgb {
+        self(value)
+    }
+}
+
+/// Colours for the characters that usually show up in puzzle maps. Anything else gets a colour
+/// derived from its code point, so distinct characters stay distinguishable.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CharPalette;
+
+impl Colormap<char> for CharPalette {
+    fn colour(&self, &value: &char) -> Rgb {
+        match value {
+            '.' => [240, 240, 240],
+            '#' => [60, 60, 60],
+            ' ' => [0, 0, 0],
+            'o' => [230, 190, 90],
+            '~' => [70, 130, 220],
+            _ => {
+                let hash = (value as u32).wrapping_mul(2_654_435_761);
+                let [r, g, b, _] = hash.to_le_bytes();
+                [r | 0x40, g | 0x40, b | 0x40]
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BoolPalette {
+    pub on: Rgb,
+    pub off: Rgb,
+}
+
+impl Default for BoolPalette {
+    fn default() -> Self {
+        Self {
+            on: [0, 0, 0],
+            off: [255, 255, 255],
+        }
+    }
+}
+
+impl Colormap<bool> for BoolPalette {
+    fn colour(&self, &value: &bool) -> Rgb {
+        if value {
+            self.on
+        } else {
+            self.off
+        }
+    }
+}
+
+/// Anything with a numeric height that can be shaded by [`Heights`]
+pub trait Height {
+    fn height(&self) -> f64;
+}
+
+macro_rules! impl_height {
+    ($($t:ty),*) => {
+        $(
+            impl Height for $t {
+                fn height(&self) -> f64 {
+                    *self as f64
+                }
+            }
+        )*
+    };
+}
+
+impl_height!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64);
+
+impl Height for char {
+    fn height(&self) -> f64 {
+        *self as u32 as f64
+    }
+}
+
+/// Shades heights from `low` to `high` along a viridis-like gradient. Values out of range are
+/// clamped to the nearest end.
+#[derive(Debug, Clone, Copy)]
+pub struct Heights {
+    low: f64,
+    high: f64,
+}
+
+impl Heights {
+    const STOPS: [Rgb; 5] = [
+        [68, 1, 84],
+        [59, 82, 139],
+        [33, 145, 140],
+        [94, 201, 98],
+        [253, 231, 37],
+    ];
+
+    pub fn new(low: impl Height, high: impl Height) -> Self {
+        Self {
+            low: low.height(),
+            high: high.height(),
+        }
+    }
+
+    /// Use the lowest and highest values of `grid` as the ends of the gradient
+    pub fn of<T: Height>(grid: &Grid<T>) -> Self {
+        let (low, high) = grid
+            .grid
+            .iter()
+            .map(Height::height)
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), height| {
+                (low.min(height), high.max(height))
+            });
+
+        Self { low, high }
+    }
+}
+
+impl<T: Height> Colormap<T> for Heights {
+    fn colour(&self, value: &T) -> Rgb {
+        let span = self.high - self.low;
+        let t = if span > 0.0 {
+            ((value.height() - self.low) / span).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let position = t * (Self::STOPS.len() - 1) as f64;
+        let index = (position as usize).min(Self::STOPS.len() - 2);
+        let fraction = position - index as f64;
+
+        let (from, to) = (Self::STOPS[index], Self::STOPS[index + 1]);
+        std::array::from_fn(|channel| {
+            (from[channel] as f64 + (to[channel] as f64 - from[channel] as f64) * fraction).round()
+                as u8
+        })
+    }
+}
+
+/// A rendered image, as rows of RGB pixels
+pub(crate) struct Image {
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) pixels: Vec<u8>,
+}
+
+impl<T> Grid<T> {
+    /// Render every square as a `scale`x`scale` block of pixels
+    pub(crate) fn render(&self, colormap: &impl Colormap<T>, scale: usize) -> Image {
+        assert!(scale > 0, "Images need a scale of at least 1");
+
+        let width = self.width() * scale;
+        let height = self.height() * scale;
+        let mut pixels = Vec::with_capacity(width * height * 3);
+
+        for y in 0..self.height() {
+            let row_start = pixels.len();
+            for x in 0..self.width() {
+                let colour = colormap.colour(&self[(x, y)]);
+                for _ in 0..scale {
+                    pixels.extend_from_slice(&colour);
+                }
+            }
+
+            for _ in 1..scale {
+                pixels.extend_from_within(row_start..row_start + width * 3);
+            }
+        }
+
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Encode as a binary PPM (P6) image
+    pub fn to_ppm(&self, colormap: &impl Colormap<T>, scale: usize) -> Vec<u8> {
+        let image = self.render(colormap, scale);
+        let mut out = format!("P6\n{} {}\n255\n", image.width, image.height).into_bytes();
+        out.extend_from_slice(&image.pixels);
+        out
+    }
+
+    /// Encode as an uncompressed PNG image
+    pub fn to_png(&self, colormap: &impl Colormap<T>, scale: usize) -> Vec<u8> {
+        let image = self.render(colormap, scale);
+        let mut png = PngWriter::new(image.width, image.height);
+        png.image_data(&image);
+        png.finish()
+    }
+
+    /// Write the grid to `path` as a PNG, or as a PPM if the extension is `.ppm`
+    pub fn save_image(
+        &self,
+        path: impl AsRef<Path>,
+        colormap: &impl Colormap<T>,
+        scale: usize,
+    ) -> io::Result<()> {
+        let path = path.as_ref();
+        let bytes = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("ppm") => self.to_ppm(colormap, scale),
+            _ => self.to_png(colormap, scale),
+        };
+
+        std::fs::write(path, bytes)
+    }
+}
+
+/// Environment variable naming the directory that [`dump_image`] writes to
+pub const IMAGE_DIR_VAR: &str = "AOC_IMAGE_DIR";
+
+/// Debugging aid for the days: when `AOC_IMAGE_DIR` is set, save `grid` as `<name>.png` in that
+/// directory. Does nothing otherwise.
+pub fn dump_image<T>(name: &str, grid: &Grid<T>, colormap: &impl Colormap<T>, scale: usize) {
+    let Some(dir) = std::env::var_os(IMAGE_DIR_VAR) else {
+        return;
+    };
+
+    let path = PathBuf::from(dir).join(format!("{name}.png"));
+    if let Err(error) = grid.save_image(&path, colormap, scale) {
+        eprintln!("Could not write {}: {error}", path.display());
+    }
+}
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                0xEDB8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+}
+
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+fn crc32(chunks: &[&[u8]]) -> u32 {
+    let mut crc = !0u32;
+    for &chunk in chunks {
+        for &byte in chunk {
+            crc = CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 is the most bytes that can be summed before `b` could overflow
+    for block in data.chunks(5552) {
+        for &byte in block {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+
+    (b << 16) | a
+}
+
+/// Wrap `data` in a zlib stream made of uncompressed deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = u16::MAX as usize;
+
+    let mut out = Vec::with_capacity(data.len() + data.len() / MAX_BLOCK * 5 + 11);
+    out.extend_from_slice(&[0x78, 0x01]);
+
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        let is_final = blocks.peek().is_none();
+        let len = block.len() as u16;
+
+        out.push(is_final as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+/// Writes PNG chunks one at a time, so animations can add their own between the image data
+pub(crate) struct PngWriter {
+    out: Vec<u8>,
+    width: usize,
+    height: usize,
+}
+
+impl PngWriter {
+    pub(crate) fn new(width: usize, height: usize) -> Self {
+        let mut writer = Self {
+            out: b"\x89PNG\r\n\x1a\n".to_vec(),
+            width,
+            height,
+        };
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&(width as u32).to_be_bytes());
+        header.extend_from_slice(&(height as u32).to_be_bytes());
+        // 8 bits per channel, RGB, default compression, filtering and no interlacing
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+        writer.chunk(b"IHDR", &header);
+
+        writer
+    }
+
+    pub(crate) fn chunk(&mut self, kind: &[u8; 4], data: &[u8]) {
+        self.out
+            .extend_from_slice(&(data.len() as u32).to_be_bytes());
+        self.out.extend_from_slice(kind);
+        self.out.extend_from_slice(data);
+        self.out
+            .extend_from_slice(&crc32(&[kind, data]).to_be_bytes());
+    }
+
+    /// The zlib-wrapped scanlines of `image`, each with the "no filter" prefix
+    pub(crate) fn compressed_scanlines(image: &Image) -> Vec<u8> {
+        if image.width == 0 || image.height == 0 {
+            // No pixels to split into rows, but every row still starts with its filter byte
+            return zlib_stored(&vec![0; image.height]);
+        }
+
+        let mut raw = Vec::with_capacity((image.width * 3 + 1) * image.height);
+        for row in image.pixels.chunks(image.width * 3).take(image.height) {
+            raw.push(0);
+            raw.extend_from_slice(row);
+        }
+
+        zlib_stored(&raw)
+    }
+
+    pub(crate) fn image_data(&mut self, image: &Image) {
+        assert_eq!((image.width, image.height), (self.width, self.height));
+        self.chunk(b"IDAT", &Self::compressed_scanlines(image));
+    }
+
+    pub(crate) fn finish(mut self) -> Vec<u8> {
+        self.chunk(b"IEND", &[]);
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(&[b"1234", b"56789"]), 0xCBF4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    #[test]
+    fn ppm_scaling() {
+        let grid: Grid<bool> = [[true, false]].into_iter().collect();
+        let ppm = grid.to_ppm(&BoolPalette::default(), 2);
+
+        let header = b"P6\n4 2\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+
+        let row = [0, 0, 0, 0, 0, 0, 255, 255, 255, 255, 255, 255];
+        assert_eq!(&ppm[header.len()..], [row, row].concat());
+    }
+
+    #[test]
+    fn png_structure() {
+        let grid: Grid<char> = ["#.", ".#"].iter().map(|row| row.chars()).collect();
+        let png = grid.to_png(&CharPalette, 3);
+
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[16..24], [0, 0, 0, 6, 0, 0, 0, 6]);
+        assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+
+        // Stored deflate blocks keep the scanlines readable: filter byte, then 6 RGB pixels
+        let idat = png.windows(4).position(|w| w == b"IDAT").unwrap() + 4;
+        let scanline = &png[idat + 7..idat + 7 + 19];
+        assert_eq!(scanline[0], 0);
+        assert_eq!(&scanline[1..4], CharPalette.colour(&'#'));
+        assert_eq!(&scanline[10..13], CharPalette.colour(&'.'));
+    }
+
+    #[test]
+    fn empty_png() {
+        for grid in [crate::grid![true; 0, 2], crate::grid![true; 3, 0]] {
+            let png = grid.to_png(&BoolPalette::default(), 2);
+            assert_eq!(png.windows(4).filter(|w| *w == b"IDAT").count(), 1);
+        }
+    }
+
+    #[test]
+    fn height_gradient() {
+        let heights = Heights::new('a', 'z');
+
+        assert_eq!(heights.colour(&'a'), Heights::STOPS[0]);
+        assert_eq!(heights.colour(&'z'), Heights::STOPS[4]);
+        assert_eq!(heights.colour(&'~'), Heights::STOPS[4]);
+        assert_eq!(Heights::new(0u8, 8u8).colour(&4u8), Heights::STOPS[2]);
+    }
+}
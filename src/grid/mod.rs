@@ -0,0 +1,454 @@
+use std::{
+    fmt::Display,
+    ops::{Index, IndexMut},
+    slice,
+};
+
+use itertools::Itertools;
+
+use crate::geom::Point;
+
+mod bits;
+mod dense;
+mod fill;
+mod image;
+mod recorder;
+mod resize;
+mod window;
+
+pub use bits::*;
+pub use dense::*;
+pub use fill::*;
+pub use image::*;
+pub use recorder::*;
+pub use resize::*;
+pub use window::*;
+
+#[macro_export]
+macro_rules! grid {
+    [$e:expr; $width:expr, $height:expr] => {
+        $crate::grid::Grid::new_filled($e, $width, $height)
+    };
+}
+
+/// How the edges of a grid connect when stepping from one position to another
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Topology {
+    /// Stepping off an edge leaves the grid
+    #[default]
+    Bounded,
+    /// Stepping off an edge of the half-open rectangle `min..max` comes back in at the opposite
+    /// edge. Positions outside the rectangle are still bounded by the grid.
+    Wrapping {
+        min: (usize, usize),
+        max: (usize, usize),
+    },
+}
+
+impl Topology {
+    /// Step from `(x, y)` by `(dx, dy)` in a grid of the given size
+    pub fn offset(
+        self,
+        (width, height): (usize, usize),
+        (x, y): (usize, usize),
+        (dx, dy): (isize, isize),
+    ) -> Option<(usize, usize)> {
+        match self {
+            Topology::Wrapping { min, max }
+                if (min.0..max.0).contains(&x) && (min.1..max.1).contains(&y) =>
+            {
+                let wrap = |pos: usize, delta: isize, min: usize, max: usize| {
+                    let span = (max - min) as isize;
+                    min + (pos as isize - min as isize + delta).rem_euclid(span) as usize
+                };
+
+                Some((wrap(x, dx, min.0, max.0), wrap(y, dy, min.1, max.1)))
+            }
+            _ => {
+                let x = x.checked_add_signed(dx).filter(|&x| x < width)?;
+                let y = y.checked_add_signed(dy).filter(|&y| y < height)?;
+                Some((x, y))
+            }
+        }
+    }
+}
+
+/// Coordinates that can be used to look up a square of a grid. Signed coordinates that fall off
+/// the grid just don't find anything.
+pub trait GridPosition: Copy {
+    fn to_grid_position(self) -> Option<(usize, usize)>;
+}
+
+impl GridPosition for (usize, usize) {
+    fn to_grid_position(self) -> Option<(usize, usize)> {
+        Some(self)
+    }
+}
+
+impl GridPosition for Point<usize> {
+    fn to_grid_position(self) -> Option<(usize, usize)> {
+        Some(self.into())
+    }
+}
+
+impl GridPosition for (isize, isize) {
+    fn to_grid_position(self) -> Option<(usize, usize)> {
+        Point::from(self).to_grid_position()
+    }
+}
+
+impl GridPosition for Point<isize> {
+    fn to_grid_position(self) -> Option<(usize, usize)> {
+        self.to_unsigned().map(Into::into)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    grid: Vec<T>,
+    topology: Topology,
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                write!(f, "{}", self[(x, y)])?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> Grid<T>
+where
+    T: Default,
+{
+    pub fn new(width: usize, height: usize) -> Self {
+        let mut grid = Vec::with_capacity(width * height);
+        grid.resize_with(width * height, T::default);
+
+        Self {
+            width,
+            height,
+            grid,
+            topology: Topology::Bounded,
+        }
+    }
+}
+
+impl<T> Grid<T>
+where
+    T: Clone,
+{
+    pub fn new_filled(element: T, width: usize, height: usize) -> Self {
+        let grid = vec![element; width * height];
+
+        Self {
+            width,
+            height,
+            grid,
+            topology: Topology::Bounded,
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn set_topology(&mut self, topology: Topology) {
+        if let Topology::Wrapping { min, max } = topology {
+            assert!(min.0 < max.0 && max.0 <= self.width());
+            assert!(min.1 < max.1 && max.1 <= self.height());
+        }
+
+        self.topology = topology;
+    }
+
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.set_topology(topology);
+        self
+    }
+
+    /// Wrap around every edge of the grid, like a torus
+    pub fn wrapping(self) -> Self {
+        let max = (self.width(), self.height());
+        self.with_topology(Topology::Wrapping { min: (0, 0), max })
+    }
+
+    /// Step from `position` by `delta`, wrapping or clipping according to the grid's topology
+    pub fn offset(
+        &self,
+        position: impl Into<(usize, usize)>,
+        delta: impl Into<(isize, isize)>,
+    ) -> Option<(usize, usize)> {
+        self.topology
+            .offset((self.width(), self.height()), position.into(), delta.into())
+    }
+
+    /// The 8 surrounding positions. With a wrapping topology narrower than 3 squares, the same
+    /// position can come up more than once.
+    pub fn neighbours(&self, position: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        let (topology, size) = (self.topology, (self.width(), self.height()));
+
+        [
+            (-1, -1),
+            (0, -1),
+            (1, -1),
+            (-1, 0),
+            (1, 0),
+            (-1, 1),
+            (0, 1),
+            (1, 1),
+        ]
+        .into_iter()
+        .filter_map(move |delta| topology.offset(size, position, delta))
+    }
+
+    pub fn neighbours_orthogonal(
+        &self,
+        position: (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let (topology, size) = (self.topology, (self.width(), self.height()));
+
+        [(-1, 0), (1, 0), (0, -1), (0, 1)]
+            .into_iter()
+            .filter_map(move |delta| topology.offset(size, position, delta))
+    }
+
+    /// Every position in row-major order
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width();
+        (0..self.grid.len()).map(move |index| (index % width, index / width))
+    }
+
+    /// Every square alongside its position, in row-major order
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.grid.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = ((usize, usize), &mut T)> {
+        self.positions().zip(self.grid.iter_mut())
+    }
+
+    /// The first position in row-major order whose square matches `predicate`
+    pub fn find(&self, mut predicate: impl FnMut(&T) -> bool) -> Option<(usize, usize)> {
+        self.iter()
+            .find(|(_, square)| predicate(square))
+            .map(|(position, _)| position)
+    }
+
+    /// Every position whose square matches `predicate`, in row-major order
+    pub fn find_all<'a>(
+        &'a self,
+        mut predicate: impl FnMut(&T) -> bool + 'a,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        self.iter()
+            .filter(move |(_, square)| predicate(square))
+            .map(|(position, _)| position)
+    }
+
+    /// How many squares match `predicate`
+    pub fn count_where(&self, mut predicate: impl FnMut(&T) -> bool) -> usize {
+        self.grid.iter().filter(|square| predicate(square)).count()
+    }
+
+    /// A grid of the same shape and topology, with `f` applied to every square
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            grid: self.grid.iter().map(f).collect(),
+            topology: self.topology,
+        }
+    }
+
+    /// Combine two grids of the same size square by square
+    pub fn zip_with<U, V>(&self, other: &Grid<U>, mut f: impl FnMut(&T, &U) -> V) -> Grid<V> {
+        assert_eq!(
+            (self.width(), self.height()),
+            (other.width(), other.height()),
+            "Zipped grids must have the same size"
+        );
+
+        Grid {
+            width: self.width,
+            height: self.height,
+            grid: self
+                .grid
+                .iter()
+                .zip(&other.grid)
+                .map(|(a, b)| f(a, b))
+                .collect(),
+            topology: self.topology,
+        }
+    }
+
+    pub fn into_flat_iter(self) -> impl DoubleEndedIterator<Item = T> {
+        self.grid.into_iter()
+    }
+
+    pub fn flat_iter_mut(&mut self) -> slice::IterMut<'_, T> {
+        self.grid.iter_mut()
+    }
+
+    pub fn get(&self, position: impl GridPosition) -> Option<&T> {
+        let (x, y) = position.to_grid_position()?;
+        if (0..self.width()).contains(&x) && (0..self.height()).contains(&y) {
+            Some(&self.grid[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, position: impl GridPosition) -> Option<&mut T> {
+        let (x, y) = position.to_grid_position()?;
+        if (0..self.width()).contains(&x) && (0..self.height()).contains(&y) {
+            Some(&mut self.grid[y * self.width + x])
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
+        &self.grid[y * self.width + x]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Self::Output {
+        &mut self.grid[y * self.width + x]
+    }
+}
+
+impl<T> Index<Point<usize>> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Point<usize>) -> &Self::Output {
+        &self[(position.x, position.y)]
+    }
+}
+
+impl<T> IndexMut<Point<usize>> for Grid<T> {
+    fn index_mut(&mut self, position: Point<usize>) -> &mut Self::Output {
+        &mut self[(position.x, position.y)]
+    }
+}
+
+impl<T, I> FromIterator<I> for Grid<T>
+where
+    I: IntoIterator<Item = T>,
+{
+    fn from_iter<S: IntoIterator<Item = I>>(iter: S) -> Self {
+        let mut height = 0;
+
+        let grid = iter
+            .into_iter()
+            .flat_map(|row_iter| {
+                height += 1; // hacky but it works!
+                row_iter
+            })
+            .collect_vec();
+
+        Self {
+            width: grid.len() / height, // TODO: check that this is actually true?
+            height,
+            grid,
+            topology: Topology::Bounded,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geom::Direction;
+
+    #[test]
+    fn wrapping_offset() {
+        let grid = grid![0u8; 4, 3].wrapping();
+
+        assert_eq!(grid.offset((0, 0), (-1, 0)), Some((3, 0)));
+        assert_eq!(grid.offset((3, 2), (1, 1)), Some((0, 0)));
+        assert_eq!(grid.offset((1, 1), (-5, 7)), Some((0, 2)));
+        assert_eq!(grid.neighbours((0, 0)).count(), 8);
+        assert_eq!(
+            grid.neighbours_orthogonal((0, 0)).collect_vec(),
+            [(3, 0), (1, 0), (0, 2), (0, 1)]
+        );
+    }
+
+    #[test]
+    fn wrapping_sub_rectangle() {
+        let grid = grid![0u8; 5, 5].with_topology(Topology::Wrapping {
+            min: (1, 1),
+            max: (4, 4),
+        });
+
+        assert_eq!(grid.offset((1, 2), (-1, 0)), Some((3, 2)));
+        assert_eq!(grid.offset((2, 3), (0, 1)), Some((2, 1)));
+        // Outside the wrapping rectangle, the grid edges still clip
+        assert_eq!(grid.offset((0, 0), (-1, 0)), None);
+        assert_eq!(grid.offset((0, 0), (1, 0)), Some((1, 0)));
+        assert_eq!(grid.neighbours_orthogonal((0, 0)).count(), 2);
+    }
+
+    #[test]
+    fn positioned_iteration() {
+        let mut grid: Grid<char> = ["a#.", "#.#"].iter().map(|row| row.chars()).collect();
+
+        assert_eq!(grid.positions().nth(4), Some((1, 1)));
+        assert_eq!(grid.iter().nth(1), Some(((1, 0), &'#')));
+        assert_eq!(grid.find(|&c| c == '#'), Some((1, 0)));
+        assert_eq!(grid.find(|&c| c == 'z'), None);
+        assert_eq!(
+            grid.find_all(|&c| c == '#').collect_vec(),
+            [(1, 0), (0, 1), (2, 1)]
+        );
+        assert_eq!(grid.count_where(|&c| c == '.'), 2);
+
+        for ((x, y), square) in grid.iter_mut() {
+            if x == y {
+                *square = 'x';
+            }
+        }
+        let walls = grid.map(|&c| c == '#');
+        assert_eq!(walls.to_string().lines().next(), Some("falsetruefalse"));
+
+        let both = grid.zip_with(&walls, |&c, &wall| if wall { '#' } else { c });
+        assert_eq!(both.to_string(), "x#.\n#x#\n");
+    }
+
+    #[test]
+    fn point_access() {
+        let mut grid = grid![0u8; 3, 2];
+        grid[Point::new(2usize, 1)] = 7;
+
+        assert_eq!(grid[(2, 1)], 7);
+        assert_eq!(grid.get(Point::new(2isize, 1)), Some(&7));
+        assert_eq!(grid.get(Point::new(-1isize, 1)), None);
+        assert_eq!(grid.get(Point::new(3isize, 1)), None);
+        assert_eq!(
+            grid.offset(Point::new(2usize, 1), Direction::Up),
+            Some((2, 0))
+        );
+    }
+}
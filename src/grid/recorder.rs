@@ -0,0 +1,605 @@
+use std::{
+    fmt::Display,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{geom::Point, prelude::HashMap};
+
+use super::{
+    image::{Image, PngWriter},
+    Colormap, Grid,
+};
+
+/// One snapshot, placed on the plane so frames of different sizes line up
+#[derive(Debug, Clone)]
+struct Frame<T> {
+    origin: Point<isize>,
+    grid: Grid<T>,
+}
+
+/// Collects snapshots of a simulation, to play back in the terminal or save as an animation
+#[derive(Debug, Clone)]
+pub struct Recorder<T> {
+    frames: Vec<Frame<T>>,
+    background: T,
+    every: usize,
+    calls: usize,
+}
+
+impl<T: Clone> Recorder<T> {
+    /// Squares that aren't covered by a frame are drawn as `background`
+    pub fn new(background: T) -> Self {
+        Self {
+            frames: Vec::new(),
+            background,
+            every: 1,
+            calls: 0,
+        }
+    }
+
+    /// Only keep one frame out of every `n` recorded
+    pub fn every(mut self, n: usize) -> Self {
+        assert!(n > 0);
+        self.every = n;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    fn push(&mut self, origin: Point<isize>, grid: impl FnOnce() -> Grid<T>) {
+        if self.calls.is_multiple_of(self.every) {
+            self.frames.push(Frame {
+                origin,
+                grid: grid(),
+            });
+        }
+        self.calls += 1;
+    }
+
+    pub fn record(&mut self, grid: &Grid<T>) {
+        self.push(Point::new(0, 0), || grid.clone());
+    }
+
+    /// Like [`Recorder::record`], but only builds the snapshot if the frame is going to be kept
+    pub fn record_with(&mut self, grid: impl FnOnce() -> Grid<T>) {
+        self.push(Point::new(0, 0), grid);
+    }
+
+    /// Record a sparse grid: every position in `cells` is drawn as `value`, on the background
+    pub fn record_sparse(&mut self, cells: impl IntoIterator<Item = Point<isize>>, value: T) {
+        let cells: Vec<_> = cells.into_iter().collect();
+        let background = self.background.clone();
+
+        let Some(min_x) = cells.iter().map(|cell| cell.x).min() else {
+            return self.push(Point::new(0, 0), || Grid::new_filled(background, 0, 0));
+        };
+        let min = Point::new(min_x, cells.iter().map(|cell| cell.y).min().unwrap());
+        let max = Point::new(
+            cells.iter().map(|cell| cell.x).max().unwrap(),
+            cells.iter().map(|cell| cell.y).max().unwrap(),
+        );
+
+        self.push(min, move || {
+            let size = max - min + Point::new(1, 1);
+            let mut grid = Grid::new_filled(background, size.x as usize, size.y as usize);
+            for cell in cells {
+                grid[(cell - min).to_unsigned().unwrap()] = value.clone();
+            }
+            grid
+        });
+    }
+
+    /// Every frame drawn on a canvas covering all of them, so they all have the same size
+    fn canvases(&self) -> impl Iterator<Item = Grid<T>> + '_ {
+        let min = Point::new(
+            self.frames
+                .iter()
+                .map(|frame| frame.origin.x)
+                .min()
+                .unwrap_or(0),
+            self.frames
+                .iter()
+                .map(|frame| frame.origin.y)
+                .min()
+                .unwrap_or(0),
+        );
+        let max = Point::new(
+            self.frames
+                .iter()
+                .map(|frame| frame.origin.x + frame.grid.width() as isize)
+                .max()
+                .unwrap_or(0),
+            self.frames
+                .iter()
+                .map(|frame| frame.origin.y + frame.grid.height() as isize)
+                .max()
+                .unwrap_or(0),
+        );
+        let size = max - min;
+
+        self.frames.iter().map(move |frame| {
+            let mut canvas =
+                Grid::new_filled(self.background.clone(), size.x as usize, size.y as usize);
+            let offset = (frame.origin - min).to_unsigned().unwrap();
+
+            for y in 0..frame.grid.height() {
+                for x in 0..frame.grid.width() {
+                    canvas[(x + offset.x, y + offset.y)] = frame.grid[(x, y)].clone();
+                }
+            }
+
+            canvas
+        })
+    }
+
+    /// Draw every frame in the terminal, `delay` apart
+    pub fn play(&self, delay: Duration) -> io::Result<()>
+    where
+        T: Display,
+    {
+        let mut stdout = io::stdout().lock();
+        for (i, canvas) in self.canvases().enumerate() {
+            // Clear the screen and move the cursor back to the top left
+            write!(
+                stdout,
+                "\x1b[2J\x1b[H{canvas}frame {}/{}",
+                i + 1,
+                self.len()
+            )?;
+            stdout.flush()?;
+            std::thread::sleep(delay);
+        }
+        writeln!(stdout)
+    }
+
+    /// Encode as a looping animated GIF. Only the first 256 distinct colours get their own
+    /// palette entry, anything past that is drawn with the closest one. Fails if the scaled frames
+    /// are more than 65535 pixels across or down, which GIFs can't describe.
+    pub fn to_gif(
+        &self,
+        colormap: &impl Colormap<T>,
+        scale: usize,
+        delay: Duration,
+    ) -> io::Result<Vec<u8>> {
+        let images: Vec<Image> = self
+            .canvases()
+            .map(|canvas| canvas.render(colormap, scale))
+            .collect();
+        let (width, height) = images
+            .first()
+            .map_or((0, 0), |image| (image.width, image.height));
+        let (Ok(width), Ok(height)) = (u16::try_from(width), u16::try_from(height)) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{width}x{height} frames are too big for a GIF"),
+            ));
+        };
+
+        let mut palette: Vec<[u8; 3]> = Vec::new();
+        let mut lookup: HashMap<[u8; 3], u8> = HashMap::default();
+        let frames: Vec<Vec<u8>> = images
+            .iter()
+            .map(|image| {
+                image
+                    .pixels
+                    .chunks(3)
+                    .map(|pixel| {
+                        let colour = [pixel[0], pixel[1], pixel[2]];
+                        *lookup.entry(colour).or_insert_with(|| {
+                            if palette.len() < 256 {
+                                palette.push(colour);
+                                (palette.len() - 1) as u8
+                            } else {
+                                closest(&palette, colour)
+                            }
+                        })
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // The colour table has 2^(n + 1) entries
+        let table_bits = (palette.len().max(2).next_power_of_two().trailing_zeros()) as u8;
+        palette.resize(1 << table_bits, [0, 0, 0]);
+
+        let mut out = b"GIF89a".to_vec();
+        out.extend_from_slice(&width.to_le_bytes());
+        out.extend_from_slice(&height.to_le_bytes());
+        out.extend_from_slice(&[0xF0 | (table_bits - 1), 0, 0]);
+        out.extend(palette.iter().flatten());
+
+        // Loop forever
+        out.extend_from_slice(b"\x21\xFF\x0BNETSCAPE2.0\x03\x01\x00\x00\x00");
+
+        let centiseconds = (delay.as_millis() / 10).min(u16::MAX as u128) as u16;
+        for indices in frames {
+            out.extend_from_slice(&[0x21, 0xF9, 0x04, 0x00]);
+            out.extend_from_slice(&centiseconds.to_le_bytes());
+            out.extend_from_slice(&[0x00, 0x00]);
+
+            out.push(0x2C);
+            out.extend_from_slice(&[0, 0, 0, 0]);
+            out.extend_from_slice(&width.to_le_bytes());
+            out.extend_from_slice(&height.to_le_bytes());
+            out.push(0);
+
+            let min_code_size = table_bits.max(2);
+            out.push(min_code_size);
+            for block in lzw_encode(&indices, min_code_size).chunks(255) {
+                out.push(block.len() as u8);
+                out.extend_from_slice(block);
+            }
+            out.push(0);
+        }
+
+        out.push(0x3B);
+        Ok(out)
+    }
+
+    /// Encode as a looping animated PNG
+    pub fn to_apng(&self, colormap: &impl Colormap<T>, scale: usize, delay: Duration) -> Vec<u8> {
+        let images: Vec<Image> = self
+            .canvases()
+            .map(|canvas| canvas.render(colormap, scale))
+            .collect();
+        let (width, height) = images
+            .first()
+            .map_or((0, 0), |image| (image.width, image.height));
+
+        let mut png = PngWriter::new(width, height);
+
+        let mut animation_control = (images.len() as u32).to_be_bytes().to_vec();
+        animation_control.extend_from_slice(&0u32.to_be_bytes());
+        png.chunk(b"acTL", &animation_control);
+
+        let delay_ms = delay.as_millis().min(u16::MAX as u128) as u16;
+        let mut sequence = 0u32;
+        for (i, image) in images.iter().enumerate() {
+            let mut frame_control = sequence.to_be_bytes().to_vec();
+            frame_control.extend_from_slice(&(width as u32).to_be_bytes());
+            frame_control.extend_from_slice(&(height as u32).to_be_bytes());
+            frame_control.extend_from_slice(&[0; 8]);
+            frame_control.extend_from_slice(&delay_ms.to_be_bytes());
+            frame_control.extend_from_slice(&1000u16.to_be_bytes());
+            // No disposal, and overwrite rather than blend
+            frame_control.extend_from_slice(&[0, 0]);
+            png.chunk(b"fcTL", &frame_control);
+            sequence += 1;
+
+            if i == 0 {
+                png.image_data(image);
+            } else {
+                let mut frame_data = sequence.to_be_bytes().to_vec();
+                frame_data.extend(PngWriter::compressed_scanlines(image));
+                png.chunk(b"fdAT", &frame_data);
+                sequence += 1;
+            }
+        }
+
+        png.finish()
+    }
+
+    /// Save as a GIF if `path` ends in `.gif`, and as an animated PNG otherwise
+    pub fn save(
+        &self,
+        path: impl AsRef<Path>,
+        colormap: &impl Colormap<T>,
+        scale: usize,
+        delay: Duration,
+    ) -> io::Result<()> {
+        let path = path.as_ref();
+        let bytes = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("gif") => self.to_gif(colormap, scale, delay)?,
+            _ => self.to_apng(colormap, scale, delay),
+        };
+
+        std::fs::write(path, bytes)
+    }
+}
+
+fn closest(palette: &[[u8; 3]], colour: [u8; 3]) -> u8 {
+    let distance = |entry: &[u8; 3]| -> u32 {
+        (0..3)
+            .map(|channel| (entry[channel] as i32 - colour[channel] as i32).pow(2) as u32)
+            .sum()
+    };
+
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, entry)| distance(entry))
+        .map_or(0, |(index, _)| index as u8)
+}
+
+/// Packs variable-width codes least significant bit first, as GIF expects
+struct BitWriter {
+    out: Vec<u8>,
+    buffer: u32,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, width: u32) {
+        self.buffer |= (code as u32) << self.bits;
+        self.bits += width;
+        while self.bits >= 8 {
+            self.out.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.out.push(self.buffer as u8);
+        }
+        self.out
+    }
+}
+
+fn lzw_encode(indices: &[u8], min_code_size: u8) -> Vec<u8> {
+    const MAX_CODE: u16 = 4096;
+
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+
+    let mut writer = BitWriter {
+        out: Vec::new(),
+        buffer: 0,
+        bits: 0,
+    };
+    let mut table: HashMap<(u16, u8), u16> = HashMap::default();
+    let mut next_code = end + 1;
+    let mut width = min_code_size as u32 + 1;
+
+    writer.write(clear, width);
+
+    let mut current: Option<u16> = None;
+    for &index in indices {
+        let Some(prefix) = current else {
+            current = Some(index as u16);
+            continue;
+        };
+
+        if let Some(&code) = table.get(&(prefix, index)) {
+            current = Some(code);
+            continue;
+        }
+
+        writer.write(prefix, width);
+        if next_code < MAX_CODE {
+            table.insert((prefix, index), next_code);
+            next_code += 1;
+            // The decoder lags one code behind, so it only widens after reading this one
+            if next_code > (1 << width) && width < 12 {
+                width += 1;
+            }
+        } else {
+            writer.write(clear, width);
+            table.clear();
+            next_code = end + 1;
+            width = min_code_size as u32 + 1;
+        }
+        current = Some(index as u16);
+    }
+
+    if let Some(prefix) = current {
+        writer.write(prefix, width);
+    }
+    writer.write(end, width);
+
+    writer.finish()
+}
+
+/// Environment variable naming the directory that [`Recording`] saves to
+pub const RECORD_DIR_VAR: &str = "AOC_RECORD_DIR";
+/// Environment variable picking how a [`Recording`] is output: `gif`, `apng` or `terminal`
+pub const RECORD_FORMAT_VAR: &str = "AOC_RECORD_FORMAT";
+
+/// Recording hook for the days. Does nothing unless `AOC_RECORD_DIR` is set, in which case the
+/// frames are saved there as `<name>.gif` (or `.png`, or played in the terminal, depending on
+/// `AOC_RECORD_FORMAT`).
+pub struct Recording<T> {
+    name: &'static str,
+    recorder: Option<Recorder<T>>,
+}
+
+impl<T: Clone> Recording<T> {
+    pub fn from_env(name: &'static str, background: T) -> Self {
+        Self {
+            name,
+            recorder: std::env::var_os(RECORD_DIR_VAR).map(|_| Recorder::new(background)),
+        }
+    }
+
+    /// Only keep one frame out of every `n` recorded
+    pub fn every(mut self, n: usize) -> Self {
+        self.recorder = self.recorder.map(|recorder| recorder.every(n));
+        self
+    }
+
+    pub fn record(&mut self, grid: &Grid<T>) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(grid);
+        }
+    }
+
+    /// Like [`Recording::record`], but only builds the snapshot if the frame is going to be kept
+    pub fn record_with(&mut self, grid: impl FnOnce() -> Grid<T>) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record_with(grid);
+        }
+    }
+
+    pub fn record_sparse(&mut self, cells: impl IntoIterator<Item = Point<isize>>, value: T) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record_sparse(cells, value);
+        }
+    }
+
+    pub fn finish(self, colormap: &impl Colormap<T>, scale: usize, delay: Duration)
+    where
+        T: Display,
+    {
+        let (Some(recorder), Some(dir)) = (self.recorder, std::env::var_os(RECORD_DIR_VAR)) else {
+            return;
+        };
+
+        let format = std::env::var(RECORD_FORMAT_VAR).unwrap_or_else(|_| "gif".to_owned());
+        let result = match format.as_str() {
+            "terminal" => recorder.play(delay),
+            "apng" | "png" => {
+                let path = PathBuf::from(dir).join(format!("{}.png", self.name));
+                recorder.save(path, colormap, scale, delay)
+            }
+            _ => {
+                let path = PathBuf::from(dir).join(format!("{}.gif", self.name));
+                recorder.save(path, colormap, scale, delay)
+            }
+        };
+
+        if let Err(error) = result {
+            eprintln!("Could not save recording {}: {error}", self.name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::BoolPalette;
+
+    /// Straightforward GIF LZW decoder, to check the encoder against
+    fn lzw_decode(data: &[u8], min_code_size: u8) -> Vec<u8> {
+        let clear = 1usize << min_code_size;
+        let end = clear + 1;
+
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let reset = |table: &mut Vec<Vec<u8>>| {
+            *table = (0..clear).map(|i| vec![i as u8]).collect();
+            table.push(Vec::new());
+            table.push(Vec::new());
+        };
+        reset(&mut table);
+
+        let mut width = min_code_size as u32 + 1;
+        let (mut buffer, mut bits, mut bytes) = (0u32, 0u32, data.iter());
+        let mut previous: Option<Vec<u8>> = None;
+        let mut out = Vec::new();
+
+        loop {
+            while bits < width {
+                buffer |= (*bytes.next().unwrap() as u32) << bits;
+                bits += 8;
+            }
+            let code = (buffer & ((1 << width) - 1)) as usize;
+            buffer >>= width;
+            bits -= width;
+
+            if code == clear {
+                reset(&mut table);
+                width = min_code_size as u32 + 1;
+                previous = None;
+                continue;
+            }
+            if code == end {
+                return out;
+            }
+
+            let entry = match (&previous, table.get(code)) {
+                (_, Some(entry)) => entry.clone(),
+                (Some(previous), None) => [previous.clone(), vec![previous[0]]].concat(),
+                (None, None) => panic!("Invalid code"),
+            };
+            out.extend_from_slice(&entry);
+
+            if let Some(previous) = previous {
+                if table.len() < 4096 {
+                    table.push([previous, vec![entry[0]]].concat());
+                }
+            }
+            if table.len() == 1 << width && width < 12 {
+                width += 1;
+            }
+            previous = Some(entry);
+        }
+    }
+
+    #[test]
+    fn lzw_round_trip() {
+        let mut seed = 12345u32;
+        let noisy: Vec<u8> = (0..20_000)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                ((seed >> 16) % 7) as u8
+            })
+            .collect();
+        let runs: Vec<u8> = (0..50_000).map(|i| ((i / 37) % 4) as u8).collect();
+
+        for data in [noisy, runs, vec![], vec![3]] {
+            assert_eq!(lzw_decode(&lzw_encode(&data, 3), 3), data);
+        }
+    }
+
+    #[test]
+    fn sparse_frames_share_a_canvas() {
+        let mut recorder = Recorder::new('.');
+        recorder.record_sparse([Point::new(0, 0), Point::new(1, 1)], '#');
+        recorder.record_sparse([Point::new(-1, 0), Point::new(2, 0)], '#');
+
+        let canvases: Vec<_> = recorder.canvases().map(|c| c.to_string()).collect();
+        assert_eq!(canvases, [".#..\n..#.\n", "#..#\n....\n"]);
+    }
+
+    #[test]
+    fn every_n_frames() {
+        let mut recorder = Recorder::new(false).every(3);
+        let grid = crate::grid![true; 2, 2];
+        for _ in 0..7 {
+            recorder.record(&grid);
+        }
+
+        assert_eq!(recorder.len(), 3);
+    }
+
+    #[test]
+    fn animation_containers() {
+        let mut recorder = Recorder::new(false);
+        recorder.record(&crate::grid![true; 2, 1]);
+        recorder.record(&crate::grid![false; 2, 1]);
+
+        let gif = recorder
+            .to_gif(&BoolPalette::default(), 1, Duration::from_millis(100))
+            .unwrap();
+        assert_eq!(&gif[..6], b"GIF89a");
+        assert_eq!(&gif[6..10], [2, 0, 1, 0]);
+        assert_eq!(gif.iter().filter(|&&byte| byte == 0x2C).count(), 2);
+        assert_eq!(gif.last(), Some(&0x3B));
+
+        let apng = recorder.to_apng(&BoolPalette::default(), 1, Duration::from_millis(100));
+        let count = |kind: &[u8]| apng.windows(4).filter(|w| *w == kind).count();
+        assert_eq!(count(b"acTL"), 1);
+        assert_eq!(count(b"fcTL"), 2);
+        assert_eq!(count(b"IDAT"), 1);
+        assert_eq!(count(b"fdAT"), 1);
+    }
+
+    #[test]
+    fn gif_frames_too_big() {
+        let mut recorder = Recorder::new(false);
+        recorder.record(&crate::grid![true; 1 << 16, 1]);
+
+        let error = recorder
+            .to_gif(&BoolPalette::default(), 1, Duration::from_millis(100))
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,231 @@
+use crate::geom::{Direction, Point};
+
+use super::{Grid, Topology};
+
+impl<T> Grid<T> {
+    /// The half-open `(min, max)` box around every square matching `predicate`, or `None` if there
+    /// aren't any
+    pub fn bounding_box(
+        &self,
+        mut predicate: impl FnMut(&T) -> bool,
+    ) -> Option<((usize, usize), (usize, usize))> {
+        self.iter()
+            .filter(|(_, square)| predicate(square))
+            .fold(None, |bounds, ((x, y), _)| {
+                let ((min_x, min_y), (max_x, max_y)) = bounds.unwrap_or(((x, y), (x, y)));
+                Some((
+                    (min_x.min(x), min_y.min(y)),
+                    (max_x.max(x + 1), max_y.max(y + 1)),
+                ))
+            })
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /// A `width` by `height` grid where `(0, 0)` of this one lands at `offset`. Squares that don't
+    /// fit are dropped, and the ones left uncovered are `fill`.
+    fn placed(&self, width: usize, height: usize, offset: (isize, isize), fill: T) -> Self {
+        let mut placed = crate::grid![fill; width, height];
+
+        for ((x, y), square) in self.iter() {
+            let position = (x as isize + offset.0, y as isize + offset.1);
+            if let Some(target) = placed.get_mut(position) {
+                *target = square.clone();
+            }
+        }
+
+        placed
+    }
+
+    /// The squares in the half-open rectangle `min..max`
+    fn sub_grid(&self, min: (usize, usize), max: (usize, usize)) -> Self {
+        self.placed(
+            max.0 - min.0,
+            max.1 - min.1,
+            (-(min.0 as isize), -(min.1 as isize)),
+            // Never used, since every square of the result is covered
+            self[(0, 0)].clone(),
+        )
+    }
+
+    /// Surround the grid with `n` squares of `fill` on every side
+    pub fn pad(&self, n: usize, fill: T) -> Self {
+        self.placed(
+            self.width() + 2 * n,
+            self.height() + 2 * n,
+            (n as isize, n as isize),
+            fill,
+        )
+    }
+
+    /// The smallest part of the grid containing every square matching `predicate`. Empty if nothing
+    /// matches.
+    pub fn crop_to(&self, predicate: impl FnMut(&T) -> bool) -> Self {
+        match self.bounding_box(predicate) {
+            Some((min, max)) => self.sub_grid(min, max),
+            None => Self {
+                width: 0,
+                height: 0,
+                grid: Vec::new(),
+                topology: Topology::Bounded,
+            },
+        }
+    }
+
+    /// Change the grid's size, keeping the `anchor` edge or corner in place, or the centre if
+    /// `anchor` is `None`. New squares are `fill`, and squares that no longer fit are dropped.
+    pub fn resize_anchored(
+        &self,
+        width: usize,
+        height: usize,
+        anchor: Option<Direction>,
+        fill: T,
+    ) -> Self {
+        let vector = anchor.map_or(Point::new(0, 0), Direction::vector);
+        let align = |old: usize, new: usize, towards: isize| {
+            let slack = new as isize - old as isize;
+            match towards {
+                -1 => 0,
+                1 => slack,
+                _ => slack / 2,
+            }
+        };
+
+        self.placed(
+            width,
+            height,
+            (
+                align(self.width(), width, vector.x),
+                align(self.height(), height, vector.y),
+            ),
+            fill,
+        )
+    }
+}
+
+/// Builds a grid from squares at any signed position, growing it as needed to fit them. Squares
+/// that are never written are filled in with a default.
+#[derive(Debug, Clone)]
+pub struct GridBuilder<T> {
+    fill: T,
+    grid: Grid<T>,
+    /// Position of the backing grid's `(0, 0)`
+    origin: Point<isize>,
+    /// Half-open bounds of the positions written or included so far
+    bounds: Option<(Point<isize>, Point<isize>)>,
+}
+
+impl<T: Clone> GridBuilder<T> {
+    pub fn new(fill: T) -> Self {
+        Self {
+            grid: crate::grid![fill.clone(); 0, 0],
+            fill,
+            origin: Point::new(0, 0),
+            bounds: None,
+        }
+    }
+
+    /// Make sure `position` is part of the final grid, without writing to it
+    pub fn include(&mut self, position: impl Into<Point<isize>>) {
+        let position = position.into();
+        let (min, max) = self.bounds.unwrap_or((position, position));
+        let (min, max) = (
+            Point::new(min.x.min(position.x), min.y.min(position.y)),
+            Point::new(max.x.max(position.x + 1), max.y.max(position.y + 1)),
+        );
+        self.bounds = Some((min, max));
+
+        let size = Point::new(self.grid.width(), self.grid.height()).to_signed();
+        let (start, end) = (self.origin, self.origin + size);
+        if min.x >= start.x && min.y >= start.y && max.x <= end.x && max.y <= end.y {
+            return;
+        }
+
+        // Grow by at least the current size on every side that's too small, so that writing
+        // squares one after another doesn't copy the whole grid every time
+        let grow = |needed: isize, slack: isize| if needed > 0 { needed + slack } else { 0 };
+        let (before, after) = (
+            Point::new(grow(start.x - min.x, size.x), grow(start.y - min.y, size.y)),
+            Point::new(grow(max.x - end.x, size.x), grow(max.y - end.y, size.y)),
+        );
+        let new_size = (size + before + after).to_unsigned().unwrap();
+
+        self.grid = self
+            .grid
+            .placed(new_size.x, new_size.y, before.into(), self.fill.clone());
+        self.origin -= before;
+    }
+
+    pub fn set(&mut self, position: impl Into<Point<isize>>, value: T) {
+        let position = position.into();
+        self.include(position);
+        self.grid[(position - self.origin).to_unsigned().unwrap()] = value;
+    }
+
+    /// Position of the built grid's `(0, 0)`
+    pub fn min(&self) -> Point<isize> {
+        self.bounds.map_or(Point::new(0, 0), |(min, _)| min)
+    }
+
+    pub fn build(self) -> Grid<T> {
+        match self.bounds {
+            Some((min, max)) => {
+                let min = (min - self.origin).to_unsigned().unwrap();
+                let max = (max - self.origin).to_unsigned().unwrap();
+                self.grid.sub_grid(min.into(), max.into())
+            }
+            None => self.grid,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rows: &[&str]) -> Grid<char> {
+        rows.iter().map(|row| row.chars()).collect()
+    }
+
+    #[test]
+    fn pad_and_crop() {
+        let original = grid(&["ab", "cd"]);
+        let padded = original.pad(1, '.');
+
+        assert_eq!(padded.to_string(), "....\n.ab.\n.cd.\n....\n");
+        assert_eq!(padded.bounding_box(|&c| c != '.'), Some(((1, 1), (3, 3))));
+        assert_eq!(padded.crop_to(|&c| c != '.').to_string(), "ab\ncd\n");
+        assert_eq!(padded.crop_to(|&c| c == 'c').to_string(), "c\n");
+        assert_eq!(padded.crop_to(|&c| c == 'z').width(), 0);
+    }
+
+    #[test]
+    fn resize() {
+        let original = grid(&["ab", "cd"]);
+
+        assert_eq!(
+            original
+                .resize_anchored(3, 3, Some(Direction::DownRight), '.')
+                .to_string(),
+            "...\n.ab\n.cd\n"
+        );
+        assert_eq!(
+            original
+                .resize_anchored(4, 1, Some(Direction::Up), '.')
+                .to_string(),
+            ".ab.\n"
+        );
+        assert_eq!(original.resize_anchored(1, 1, None, '.').to_string(), "a\n");
+    }
+
+    #[test]
+    fn builder_grows() {
+        let mut builder = GridBuilder::new('.');
+        builder.set((2isize, 1), 'a');
+        builder.set((-1isize, 3), 'b');
+        builder.include((0isize, 0));
+
+        assert_eq!(builder.min(), Point::new(-1, 0));
+        assert_eq!(builder.build().to_string(), "....\n...a\n....\nb...\n");
+    }
+}
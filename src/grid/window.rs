@@ -0,0 +1,469 @@
+use std::{
+    fmt::Display,
+    ops::{Index, IndexMut},
+};
+
+use super::{Grid, Topology};
+
+/// The half-open rectangle `min..max` of a grid. Positions are relative to `min`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Bounds {
+    min: (usize, usize),
+    max: (usize, usize),
+}
+
+impl Bounds {
+    fn new<T>(grid: &Grid<T>, min: (usize, usize), max: (usize, usize)) -> Self {
+        assert!(
+            min.0 <= max.0 && max.0 <= grid.width(),
+            "Window columns {}..{} don't fit in a grid of width {}",
+            min.0,
+            max.0,
+            grid.width()
+        );
+        assert!(
+            min.1 <= max.1 && max.1 <= grid.height(),
+            "Window rows {}..{} don't fit in a grid of height {}",
+            min.1,
+            max.1,
+            grid.height()
+        );
+
+        Self { min, max }
+    }
+
+    fn width(self) -> usize {
+        self.max.0 - self.min.0
+    }
+
+    fn height(self) -> usize {
+        self.max.1 - self.min.1
+    }
+
+    fn contains(self, (x, y): (usize, usize)) -> bool {
+        x < self.width() && y < self.height()
+    }
+
+    fn to_grid(self, (x, y): (usize, usize)) -> (usize, usize) {
+        (x + self.min.0, y + self.min.1)
+    }
+
+    /// A rectangle within this one, in local coordinates
+    fn sub_bounds(self, min: (usize, usize), max: (usize, usize)) -> Self {
+        assert!(min.0 <= max.0 && max.0 <= self.width());
+        assert!(min.1 <= max.1 && max.1 <= self.height());
+
+        Self {
+            min: self.to_grid(min),
+            max: self.to_grid(max),
+        }
+    }
+
+    /// Grow by `n` on every side, stopping at the edges of the grid
+    fn grow<T>(self, grid: &Grid<T>, n: usize) -> Self {
+        Self {
+            min: (self.min.0.saturating_sub(n), self.min.1.saturating_sub(n)),
+            max: (
+                self.max.0.saturating_add(n).min(grid.width()),
+                self.max.1.saturating_add(n).min(grid.height()),
+            ),
+        }
+    }
+
+    fn positions(self) -> impl Iterator<Item = (usize, usize)> {
+        (0..self.height()).flat_map(move |y| (0..self.width()).map(move |x| (x, y)))
+    }
+
+    fn neighbours(
+        self,
+        position: (usize, usize),
+        deltas: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let size = (self.width(), self.height());
+
+        deltas
+            .iter()
+            .filter_map(move |&delta| Topology::Bounded.offset(size, position, delta))
+    }
+}
+
+const NEIGHBOURS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+const NEIGHBOURS_ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+fn fmt_window<T: Display>(
+    f: &mut std::fmt::Formatter<'_>,
+    grid: &Grid<T>,
+    bounds: Bounds,
+) -> std::fmt::Result {
+    for y in 0..bounds.height() {
+        for x in 0..bounds.width() {
+            write!(f, "{}", grid[bounds.to_grid((x, y))])?;
+        }
+        writeln!(f)?;
+    }
+
+    Ok(())
+}
+
+/// A read-only view of a rectangle of a grid
+pub struct Window<'a, T> {
+    grid: &'a Grid<T>,
+    bounds: Bounds,
+}
+
+// Not derived, since that would require `T: Clone`
+impl<'a, T> Clone for Window<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for Window<'a, T> {}
+
+impl<'a, T> Window<'a, T> {
+    /// The half-open rectangle `min..max` of `grid`
+    pub fn new(grid: &'a Grid<T>, min: (usize, usize), max: (usize, usize)) -> Self {
+        Self {
+            bounds: Bounds::new(grid, min, max),
+            grid,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.bounds.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.bounds.height()
+    }
+
+    /// Top left corner of the window in the underlying grid
+    pub fn min(&self) -> (usize, usize) {
+        self.bounds.min
+    }
+
+    /// One past the bottom right corner of the window in the underlying grid
+    pub fn max(&self) -> (usize, usize) {
+        self.bounds.max
+    }
+
+    /// Translate a position in the window to a position in the underlying grid
+    pub fn to_grid_position(&self, position: (usize, usize)) -> (usize, usize) {
+        self.bounds.to_grid(position)
+    }
+
+    pub fn get(&self, position: (usize, usize)) -> Option<&'a T> {
+        self.bounds
+            .contains(position)
+            .then(|| &self.grid[self.bounds.to_grid(position)])
+    }
+
+    /// The half-open rectangle `min..max` of this window, in window coordinates
+    pub fn window(&self, min: (usize, usize), max: (usize, usize)) -> Window<'a, T> {
+        Self {
+            grid: self.grid,
+            bounds: self.bounds.sub_bounds(min, max),
+        }
+    }
+
+    pub fn grow_once(self) -> Self {
+        self.grow(1)
+    }
+
+    /// Grow by `n` squares on every side, stopping at the edges of the grid
+    pub fn grow(self, n: usize) -> Self {
+        Self {
+            grid: self.grid,
+            bounds: self.bounds.grow(self.grid, n),
+        }
+    }
+
+    /// Every position in the window, in row-major order
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        self.bounds.positions()
+    }
+
+    /// Every square in the window with its position, in row-major order
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &'a T)> {
+        let (grid, bounds) = (self.grid, self.bounds);
+        bounds
+            .positions()
+            .map(move |position| (position, &grid[bounds.to_grid(position)]))
+    }
+
+    /// Same as [`Grid::neighbours`], but clipped to the window
+    pub fn neighbours(&self, position: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        self.bounds.neighbours(position, &NEIGHBOURS)
+    }
+
+    /// Same as [`Grid::neighbours_orthogonal`], but clipped to the window
+    pub fn neighbours_orthogonal(
+        &self,
+        position: (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> {
+        self.bounds.neighbours(position, &NEIGHBOURS_ORTHOGONAL)
+    }
+}
+
+impl<'a, T> Index<(usize, usize)> for Window<'a, T> {
+    type Output = T;
+
+    fn index(&self, position: (usize, usize)) -> &Self::Output {
+        self.get(position).expect("Position is outside the window")
+    }
+}
+
+impl<'a, T: Display> Display for Window<'a, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt_window(f, self.grid, self.bounds)
+    }
+}
+
+/// A mutable view of a rectangle of a grid
+pub struct WindowMut<'a, T> {
+    grid: &'a mut Grid<T>,
+    bounds: Bounds,
+}
+
+impl<'a, T> WindowMut<'a, T> {
+    /// The half-open rectangle `min..max` of `grid`
+    pub fn new(grid: &'a mut Grid<T>, min: (usize, usize), max: (usize, usize)) -> Self {
+        Self {
+            bounds: Bounds::new(grid, min, max),
+            grid,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.bounds.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.bounds.height()
+    }
+
+    /// Top left corner of the window in the underlying grid
+    pub fn min(&self) -> (usize, usize) {
+        self.bounds.min
+    }
+
+    /// One past the bottom right corner of the window in the underlying grid
+    pub fn max(&self) -> (usize, usize) {
+        self.bounds.max
+    }
+
+    /// Translate a position in the window to a position in the underlying grid
+    pub fn to_grid_position(&self, position: (usize, usize)) -> (usize, usize) {
+        self.bounds.to_grid(position)
+    }
+
+    /// Borrow as a read-only window over the same rectangle
+    pub fn as_window(&self) -> Window<'_, T> {
+        Window {
+            grid: self.grid,
+            bounds: self.bounds,
+        }
+    }
+
+    pub fn get(&self, position: (usize, usize)) -> Option<&T> {
+        self.bounds
+            .contains(position)
+            .then(|| &self.grid[self.bounds.to_grid(position)])
+    }
+
+    pub fn get_mut(&mut self, position: (usize, usize)) -> Option<&mut T> {
+        self.bounds
+            .contains(position)
+            .then(|| &mut self.grid[self.bounds.to_grid(position)])
+    }
+
+    /// The half-open rectangle `min..max` of this window, in window coordinates
+    pub fn window(&self, min: (usize, usize), max: (usize, usize)) -> Window<'_, T> {
+        self.as_window().window(min, max)
+    }
+
+    /// The half-open rectangle `min..max` of this window, in window coordinates
+    pub fn window_mut(&mut self, min: (usize, usize), max: (usize, usize)) -> WindowMut<'_, T> {
+        WindowMut {
+            bounds: self.bounds.sub_bounds(min, max),
+            grid: self.grid,
+        }
+    }
+
+    pub fn grow_once(self) -> Self {
+        self.grow(1)
+    }
+
+    /// Grow by `n` squares on every side, stopping at the edges of the grid
+    pub fn grow(self, n: usize) -> Self {
+        Self {
+            bounds: self.bounds.grow(self.grid, n),
+            grid: self.grid,
+        }
+    }
+
+    /// Every position in the window, in row-major order
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        self.bounds.positions()
+    }
+
+    /// Every square in the window with its position, in row-major order
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.as_window().iter()
+    }
+
+    /// Every square in the window with its position, in row-major order
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = ((usize, usize), &mut T)> {
+        let Bounds { min, max } = self.bounds;
+        let width = self.grid.width();
+
+        self.grid
+            .grid
+            .chunks_mut(width.max(1))
+            .skip(min.1)
+            .take(max.1 - min.1)
+            .enumerate()
+            .flat_map(move |(y, row)| {
+                row[min.0..max.0]
+                    .iter_mut()
+                    .enumerate()
+                    .map(move |(x, square)| ((x, y), square))
+            })
+    }
+
+    pub fn fill(&mut self, value: T)
+    where
+        T: Clone,
+    {
+        for (_, square) in self.iter_mut() {
+            *square = value.clone();
+        }
+    }
+
+    /// Same as [`Grid::neighbours`], but clipped to the window
+    pub fn neighbours(&self, position: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        self.bounds.neighbours(position, &NEIGHBOURS)
+    }
+
+    /// Same as [`Grid::neighbours_orthogonal`], but clipped to the window
+    pub fn neighbours_orthogonal(
+        &self,
+        position: (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> {
+        self.bounds.neighbours(position, &NEIGHBOURS_ORTHOGONAL)
+    }
+}
+
+impl<'a, T> Index<(usize, usize)> for WindowMut<'a, T> {
+    type Output = T;
+
+    fn index(&self, position: (usize, usize)) -> &Self::Output {
+        self.get(position).expect("Position is outside the window")
+    }
+}
+
+impl<'a, T> IndexMut<(usize, usize)> for WindowMut<'a, T> {
+    fn index_mut(&mut self, position: (usize, usize)) -> &mut Self::Output {
+        self.get_mut(position)
+            .expect("Position is outside the window")
+    }
+}
+
+impl<'a, T: Display> Display for WindowMut<'a, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt_window(f, self.grid, self.bounds)
+    }
+}
+
+impl<T> Grid<T> {
+    /// A read-only view of the half-open rectangle `min..max`
+    pub fn window(&self, min: (usize, usize), max: (usize, usize)) -> Window<'_, T> {
+        Window::new(self, min, max)
+    }
+
+    /// A mutable view of the half-open rectangle `min..max`
+    pub fn window_mut(&mut self, min: (usize, usize), max: (usize, usize)) -> WindowMut<'_, T> {
+        WindowMut::new(self, min, max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::Itertools;
+
+    fn numbered(width: usize, height: usize) -> Grid<usize> {
+        (0..height)
+            .map(|y| (0..width).map(move |x| y * width + x))
+            .collect()
+    }
+
+    #[test]
+    fn bounds_are_half_open() {
+        let grid = numbered(5, 4);
+        let window = grid.window((1, 1), (4, 3));
+
+        assert_eq!((window.width(), window.height()), (3, 2));
+        assert_eq!(window[(0, 0)], 6);
+        assert_eq!(window.get((2, 1)), Some(&13));
+        assert_eq!(window.get((3, 0)), None);
+        assert_eq!(window.to_string(), "678\n111213\n");
+        assert_eq!(
+            window.iter().map(|(_, &n)| n).collect_vec(),
+            [6, 7, 8, 11, 12, 13]
+        );
+    }
+
+    #[test]
+    fn grow_is_clamped() {
+        let mut grid = numbered(5, 4);
+        let window = grid.window_mut((0, 1), (2, 2)).grow_once();
+        assert_eq!((window.min(), window.max()), ((0, 0), (3, 3)));
+
+        let window = window.grow(10);
+        assert_eq!((window.min(), window.max()), ((0, 0), (5, 4)));
+    }
+
+    #[test]
+    fn nested_windows() {
+        let mut grid = numbered(6, 6);
+        let mut outer = grid.window_mut((1, 1), (5, 5));
+        let mut inner = outer.window_mut((1, 1), (3, 3));
+
+        assert_eq!(inner.to_grid_position((0, 0)), (2, 2));
+        inner.fill(0);
+        assert_eq!(outer.window((0, 0), (4, 1)).iter().count(), 4);
+        assert_eq!(outer[(1, 1)], 0);
+        assert_eq!(outer[(0, 0)], 7);
+
+        for ((x, y), square) in outer.iter_mut() {
+            *square = x + 10 * y;
+        }
+        assert_eq!(grid[(4, 3)], 23);
+        assert_eq!(grid[(0, 0)], 0);
+        assert_eq!(grid[(5, 5)], 35);
+    }
+
+    #[test]
+    fn neighbours_stay_inside() {
+        let grid = numbered(5, 5);
+        let window = grid.window((1, 1), (4, 4));
+
+        assert_eq!(window.neighbours((0, 0)).count(), 3);
+        assert_eq!(window.neighbours((1, 1)).count(), 8);
+        assert_eq!(
+            window.neighbours_orthogonal((2, 0)).collect_vec(),
+            [(1, 0), (2, 1)]
+        );
+    }
+}
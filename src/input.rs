@@ -0,0 +1,229 @@
+//! Tidying up puzzle inputs, whatever line endings and stray whitespace they were saved with.
+
+use std::{
+    borrow::Cow,
+    io::{self, BufRead},
+};
+
+use crate::{parse::ParseError, Solution};
+
+/// `input` with `\n` line endings, no whitespace at the end of any line, no blank lines at the end,
+/// and a single `\n` after the last line. Already tidy input is borrowed rather than copied.
+pub fn normalize(input: &str) -> Cow<'_, str> {
+    let trimmed = input.trim_end();
+    if trimmed.is_empty() {
+        return Cow::Borrowed("");
+    }
+
+    let tidy = input.len() == trimmed.len() + 1
+        && input.ends_with('\n')
+        && !input.contains('\r')
+        && trimmed
+            .lines()
+            .all(|line| line.len() == line.trim_end().len());
+    if tidy {
+        return Cow::Borrowed(input);
+    }
+
+    let mut normalized = String::with_capacity(input.len());
+    for line in trimmed.lines() {
+        normalized.push_str(line.trim_end());
+        normalized.push('\n');
+    }
+    Cow::Owned(normalized)
+}
+
+/// The lines of `bytes`, without their line endings or any whitespace at the end. Like
+/// [`str::lines`], a final line ending doesn't start another, empty, line.
+pub fn lines(bytes: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let empty = bytes.is_empty();
+    let bytes = bytes.strip_suffix(b"\n").unwrap_or(bytes);
+    bytes
+        .split(|&byte| byte == b'\n')
+        .map(<[u8]>::trim_ascii_end)
+        .filter(move |_| !empty)
+}
+
+/// A solution that only ever needs to see one line of its input at a time, so it can be streamed
+/// inputs far too big to hold in memory
+pub trait LineByLine: Default {
+    /// Take in the next line, tidied up like those from [`lines`]
+    fn line<'a>(&mut self, line: &'a [u8]) -> Result<(), ParseError<'a>>;
+
+    /// Both answers, or what's missing if the input stopped partway through something
+    fn finish(self) -> Result<(Solution, Solution), ParseError<'static>>;
+}
+
+/// Feed every line of `input` to a new `S`
+pub fn solve<S: LineByLine>(input: &str) -> (Solution, Solution) {
+    let mut solver = S::default();
+    for line in lines(input.as_bytes()) {
+        solver
+            .line(line)
+            .unwrap_or_else(|error| error.report(input));
+    }
+    solver.finish().unwrap_or_else(|error| panic!("{error}"))
+}
+
+/// Like [`solve`], but reading only a line of `input` at a time
+pub fn solve_streaming<S: LineByLine>(input: &mut dyn BufRead) -> io::Result<(Solution, Solution)> {
+    let mut solver = S::default();
+    let mut buffer = Vec::new();
+    for line_number in 1.. {
+        buffer.clear();
+        if input.read_until(b'\n', &mut buffer)? == 0 {
+            break;
+        }
+
+        let line = buffer
+            .strip_suffix(b"\n")
+            .unwrap_or(&buffer)
+            .trim_ascii_end();
+        solver
+            .line(line)
+            .unwrap_or_else(|error| error.report_line(line, line_number));
+    }
+    Ok(solver.finish().unwrap_or_else(|error| panic!("{error}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::{day01, day02, day03, day04, day05, day06, day10, day18};
+
+    /// The same input saved in ways the byte parsers have to cope with
+    fn variants(input: &str) -> [String; 5] {
+        [
+            input.to_owned(),
+            input.replace('\n', "\r\n"),
+            input.trim_end().to_owned(),
+            input.replace('\n', " \t\n"),
+            format!("{input}\n\n"),
+        ]
+    }
+
+    #[test]
+    fn normalizes() {
+        for variant in variants("a\n\n  b\n") {
+            assert_eq!(normalize(&variant), "a\n\n  b\n");
+        }
+        assert!(matches!(normalize("a\nb\n"), Cow::Borrowed(_)));
+        assert_eq!(normalize(" \r\n\n"), "");
+        assert_eq!(normalize("x\r"), "x\n");
+    }
+
+    #[test]
+    fn splits_lines() {
+        for variant in variants("a\n\n  b\n") {
+            let lines = lines(variant.as_bytes()).collect_vec();
+            assert_eq!(&lines[..3], [&b"a"[..], b"", b"  b"]);
+            assert!(lines[3..].iter().all(|line| line.is_empty()));
+        }
+        assert_eq!(lines(b"").count(), 0);
+        assert_eq!(lines(b"\n").collect_vec(), [b""]);
+    }
+
+    #[test]
+    fn byte_parsers_tolerate_variants() {
+        type Day = fn(&str) -> (Solution, Solution);
+        let cases: [(Day, &str, (Solution, Solution)); 5] = [
+            (
+                day01::run,
+                "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000\n",
+                (24000.into(), 45000.into()),
+            ),
+            (day02::run, "A Y\nB X\nC Z\n", (15.into(), 12.into())),
+            (
+                day04::run,
+                "2-4,6-8\n2-3,4-5\n5-7,7-9\n2-8,3-7\n6-6,4-6\n2-6,4-8\n",
+                (2.into(), 4.into()),
+            ),
+            (
+                day05::run,
+                "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\n\
+                 move 1 from 2 to 1\nmove 3 from 1 to 3\nmove 2 from 2 to 1\nmove 1 from 1 to 2\n",
+                ("CMZ".into(), "MCD".into()),
+            ),
+            (
+                day18::run,
+                "2,2,2\n1,2,2\n3,2,2\n2,1,2\n2,3,2\n2,2,1\n2,2,3\n2,2,4\n2,2,6\n1,2,5\n3,2,5\n\
+                 2,1,5\n2,3,5\n-1,-1,-1\n",
+                (70.into(), 64.into()),
+            ),
+        ];
+
+        for (run, input, expected) in cases {
+            for variant in variants(input) {
+                assert_eq!(run(&variant), expected, "{variant:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn streaming_matches_in_memory() {
+        type Day = fn(&str) -> (Solution, Solution);
+        type StreamingDay = fn(&mut dyn BufRead) -> io::Result<(Solution, Solution)>;
+        let cases: [(Day, StreamingDay, &str); 6] = [
+            (day01::run, day01::run_streaming, "1\n2\n\n4\n\n3\n3\n"),
+            (day02::run, day02::run_streaming, "A Y\nB X\nC Z\n"),
+            (
+                day03::run,
+                day03::run_streaming,
+                "vJrwpWtwJgWrhcsFMMfFFhFp\njqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL\nPmmdzqPrVvPwwTWBwg\n",
+            ),
+            (day04::run, day04::run_streaming, "2-4,6-8\n2-8,3-7\n"),
+            (
+                day06::run,
+                day06::run_streaming,
+                "mjqjpqmgbljsphdztnvjfqwrcgsmlb\n",
+            ),
+            (day10::run, day10::run_streaming, "noop\naddx 3\naddx -5\n"),
+        ];
+
+        for (run, run_streaming, input) in cases {
+            for variant in variants(input) {
+                let streamed = run_streaming(&mut variant.as_bytes()).unwrap();
+                assert_eq!(streamed, run(&variant), "{variant:?}");
+            }
+        }
+
+        let error = day06::run_streaming(&mut &b"abab"[..]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn reports_bad_lines() {
+        type Day = fn(&str) -> (Solution, Solution);
+        let cases: [(Day, &str, &str); 5] = [
+            (
+                day02::run,
+                "A Y\nD X\n",
+                "invalid opponent's play at line 2, column 1",
+            ),
+            (
+                day02::run,
+                "A Yy\n",
+                "unexpected trailing input at line 1, column 4",
+            ),
+            (day03::run, "ab1b\n", "invalid item at line 1, column 3"),
+            (
+                day03::run,
+                "vJrwpWtwJgWrhcsFMMfFFhFp\n",
+                "incomplete group of three rucksacks at the end of the input",
+            ),
+            (
+                day10::run,
+                "noop\nmulx 3\n",
+                "invalid instruction at line 2, column 1",
+            ),
+        ];
+
+        for (run, input, expected) in cases {
+            let panic = std::panic::catch_unwind(|| run(input)).unwrap_err();
+            let message = panic.downcast::<String>().unwrap();
+            assert_eq!(message.lines().next(), Some(expected), "{input:?}");
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//! Sets of integers stored as sorted, disjoint ranges, for when there are far too many values to
+//! store one by one.
+
+use std::ops::{Add, RangeInclusive, Sub};
+
+/// An integer type whose values (all but the largest) have a next value
+pub trait Successor: Sized {
+    fn checked_successor(self) -> Option<Self>;
+}
+
+macro_rules! impl_successor {
+    ($($int:ty),*) => {
+        $(
+            impl Successor for $int {
+                fn checked_successor(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+            }
+        )*
+    };
+}
+
+impl_successor!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+/// A set of integers, kept as the fewest possible ranges. Ranges that touch or overlap are merged
+/// as they're inserted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    /// Half-open, sorted, and with a gap of at least one value between any two
+    ranges: Vec<(T, T)>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self { ranges: Vec::new() }
+    }
+}
+
+impl<T> IntervalSet<T>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T> + From<u8> + Successor,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Half-open form of `range`, or `None` if it's empty. Ranges running up to the largest
+    /// value of `T` have no end to store, so they're rejected.
+    fn half_open(range: RangeInclusive<T>) -> Option<(T, T)> {
+        let (start, end) = range.into_inner();
+        let end = end
+            .checked_successor()
+            .expect("ranges can't include the largest value of their type");
+        (start < end).then_some((start, end))
+    }
+
+    pub fn insert(&mut self, range: RangeInclusive<T>) {
+        let Some((start, end)) = Self::half_open(range) else {
+            return;
+        };
+
+        // Every range from `first` up to `last` touches the new one
+        let first = self.ranges.partition_point(|&(_, e)| e < start);
+        let last = self.ranges.partition_point(|&(s, _)| s <= end);
+
+        let merged = if first < last {
+            (
+                start.min(self.ranges[first].0),
+                end.max(self.ranges[last - 1].1),
+            )
+        } else {
+            (start, end)
+        };
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    pub fn remove(&mut self, range: RangeInclusive<T>) {
+        let Some((start, end)) = Self::half_open(range) else {
+            return;
+        };
+
+        // Every range from `first` up to `last` overlaps the removed one
+        let first = self.ranges.partition_point(|&(_, e)| e <= start);
+        let last = self.ranges.partition_point(|&(s, _)| s < end);
+        if first == last {
+            return;
+        }
+
+        let before = (self.ranges[first].0 < start).then(|| (self.ranges[first].0, start));
+        let after = (self.ranges[last - 1].1 > end).then(|| (end, self.ranges[last - 1].1));
+        self.ranges
+            .splice(first..last, before.into_iter().chain(after));
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let index = self.ranges.partition_point(|&(_, end)| end <= value);
+        self.ranges
+            .get(index)
+            .is_some_and(|&(start, _)| start <= value)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = self.clone();
+        for range in other.iter() {
+            union.insert(range);
+        }
+        union
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+
+        while let (Some(&a), Some(&b)) = (self.ranges.get(i), other.ranges.get(j)) {
+            let (start, end) = (a.0.max(b.0), a.1.min(b.1));
+            if start < end {
+                ranges.push((start, end));
+            }
+
+            if a.1 < b.1 {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        Self { ranges }
+    }
+
+    /// Every value in `bounds` that isn't in the set
+    pub fn complement(&self, bounds: RangeInclusive<T>) -> Self {
+        let mut complement = Self::from(bounds);
+        for range in self.iter() {
+            complement.remove(range);
+        }
+        complement
+    }
+
+    /// The number of values in the set
+    pub fn len(&self) -> T {
+        self.ranges
+            .iter()
+            .fold(T::from(0), |total, &(start, end)| total + (end - start))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The ranges making up the set, in order
+    pub fn iter(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.ranges
+            .iter()
+            .map(|&(start, end)| start..=end - T::from(1))
+    }
+
+    /// The missing values between the first and last values in the set, in order
+    pub fn gaps(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.ranges
+            .windows(2)
+            .map(|pair| pair[0].1..=pair[1].0 - T::from(1))
+    }
+}
+
+impl<T> From<RangeInclusive<T>> for IntervalSet<T>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T> + From<u8> + Successor,
+{
+    fn from(range: RangeInclusive<T>) -> Self {
+        let mut set = Self::new();
+        set.insert(range);
+        set
+    }
+}
+
+impl<T> FromIterator<RangeInclusive<T>> for IntervalSet<T>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T> + From<u8> + Successor,
+{
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(ranges: I) -> Self {
+        let mut set = Self::new();
+        for range in ranges {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    #[test]
+    fn insert_merges() {
+        let mut set: IntervalSet<isize> = [1..=3, 10..=12, 6..=7].into_iter().collect();
+        assert_eq!(set.iter().collect_vec(), [1..=3, 6..=7, 10..=12]);
+        assert_eq!(set.gaps().collect_vec(), [4..=5, 8..=9]);
+
+        // Touching ranges merge as well as overlapping ones
+        set.insert(4..=5);
+        set.insert(8..=8);
+        assert_eq!(set.iter().collect_vec(), [1..=8, 10..=12]);
+
+        set.insert(-5..=20);
+        assert_eq!(set.iter().collect_vec(), [-5..=20]);
+        assert_eq!(set.len(), 26);
+
+        let (start, end) = (30, 29);
+        set.insert(start..=end);
+        assert_eq!(set.iter().count(), 1);
+    }
+
+    #[test]
+    fn remove_splits() {
+        let mut set = IntervalSet::from(0..=10isize);
+        set.remove(3..=4);
+        set.remove(10..=15);
+        assert_eq!(set.iter().collect_vec(), [0..=2, 5..=9]);
+        assert!(set.contains(2));
+        assert!(!set.contains(3));
+        assert!(!set.contains(10));
+
+        set.remove(-1..=6);
+        assert_eq!(set.iter().collect_vec(), [7..=9]);
+        set.remove(7..=9);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn set_operations() {
+        let a: IntervalSet<usize> = [0..=4, 8..=12].into_iter().collect();
+        let b: IntervalSet<usize> = [3..=9, 12..=20].into_iter().collect();
+
+        assert_eq!(
+            a.intersection(&b).iter().collect_vec(),
+            [3..=4, 8..=9, 12..=12]
+        );
+        assert_eq!(a.union(&b).iter().collect_vec(), [0..=20]);
+        assert_eq!(a.complement(2..=15).iter().collect_vec(), [5..=7, 13..=15]);
+        assert_eq!(a.complement(2..=15).len(), 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "largest value")]
+    fn rejects_unbounded_ranges() {
+        IntervalSet::new().insert(1..=u8::MAX);
+    }
+}
@@ -1,15 +1,42 @@
 use std::{
     fmt::Display,
+    io::{self, BufRead},
     path::{Path, PathBuf},
     str::FromStr,
 };
 
 use itertools::Itertools;
-use nom::{combinator::map, IResult};
+
+mod automaton;
+mod balanced;
+// A full set API, only part of which the days use so far
+#[allow(dead_code)]
+mod bitset;
+mod branch_and_bound;
+mod cycle;
+mod expr;
+mod geom;
+mod input;
+mod interval;
+// Number theory helpers, not all of them needed by a day yet
+#[allow(dead_code)]
+mod math;
+// A general-purpose cache, with backends not every day needs
+#[allow(dead_code)]
+mod memo;
+mod parse;
+// Dijkstra and A* are ready for the days that will need them
+#[allow(dead_code)]
+mod search;
+// Some of the list operations are there for completeness, not for a day
+#[allow(dead_code)]
+mod sequence;
 
 #[macro_use]
 mod grid;
 
+mod cube;
+
 #[macro_use]
 pub mod prelude;
 
@@ -39,7 +66,10 @@ mod day23;
 mod day24;
 mod day25;
 
-const DAY_TABLE: &[fn(&str) -> (Solution, Solution)] = &[
+type Day = fn(&str) -> (Solution, Solution);
+type StreamingDay = fn(&mut dyn BufRead) -> io::Result<(Solution, Solution)>;
+
+const DAY_TABLE: &[Day] = &[
     day01::run,
     day02::run,
     day03::run,
@@ -67,6 +97,16 @@ const DAY_TABLE: &[fn(&str) -> (Solution, Solution)] = &[
     day25::run,
 ];
 
+/// The days that can solve their input without ever holding all of it
+const STREAMING_DAY_TABLE: &[(u32, StreamingDay)] = &[
+    (1, day01::run_streaming),
+    (2, day02::run_streaming),
+    (3, day03::run_streaming),
+    (4, day04::run_streaming),
+    (6, day06::run_streaming),
+    (10, day10::run_streaming),
+];
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum Solution {
     Int(usize),
@@ -114,14 +154,25 @@ impl Display for Solution {
 pub fn get_input(day: u32) -> String {
     let crate_root = Path::new(env!("CARGO_MANIFEST_DIR"));
     let input_file = crate_root.join("input").join(format!("{day}.input"));
-    std::fs::read_to_string(input_file)
-        .expect("Could not read from input file")
-        .to_owned()
+    let input = std::fs::read_to_string(input_file).expect("Could not read from input file");
+    input::normalize(&input).into_owned()
 }
 
 /// Run a specific day with the given input as a string
 pub fn run_day(day: u32, input: &str) -> (Solution, Solution) {
-    DAY_TABLE[day as usize - 1](input)
+    DAY_TABLE[day as usize - 1](&input::normalize(input))
+}
+
+/// Run a specific day, reading its input as it goes rather than all at once. `None` if the day
+/// needs its whole input.
+pub fn run_day_streaming(
+    day: u32,
+    mut input: impl BufRead,
+) -> Option<io::Result<(Solution, Solution)>> {
+    STREAMING_DAY_TABLE
+        .iter()
+        .find(|&&(streaming_day, _)| streaming_day == day)
+        .map(|(_, run)| run(&mut input))
 }
 
 pub fn output_file_path(day: u32) -> PathBuf {
@@ -140,7 +191,3 @@ pub fn get_expected_output(day: u32) -> (Solution, Solution) {
         .collect_tuple()
         .unwrap()
 }
-
-pub fn parse_usize(input: &str) -> IResult<&str, usize> {
-    map(nom::character::complete::u64, |n| n as usize)(input)
-}
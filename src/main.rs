@@ -1,6 +1,11 @@
-use std::time::{Duration, Instant};
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
-use advent_of_code_2022::{get_input, output_file_path, run_day};
+use advent_of_code_2022::{get_input, output_file_path, run_day, run_day_streaming, Solution};
 use chrono::{Datelike, FixedOffset, Utc};
 use clap::Parser;
 
@@ -12,9 +17,15 @@ enum Options {
         day: u32,
         #[clap(short, long)]
         save: bool,
+        /// Solve this file instead of the day's usual input
+        #[clap(short, long, conflicts_with = "save")]
+        input: Option<PathBuf>,
     },
 }
 
+/// Inputs at least this big are read a bit at a time by the days that can manage that
+const STREAMING_THRESHOLD: u64 = 64 * 1024 * 1024;
+
 fn current_day_december_2022() -> Option<u32> {
     let eastern_tz = FixedOffset::west_opt(5 * 3600).unwrap(); // Eastern Canada / US
     let date = Utc::now().with_timezone(&eastern_tz).date_naive();
@@ -35,7 +46,7 @@ impl Options {
                     today.expect("This option only works from December 1st through 25th, 2022");
                 vec![today]
             }
-            Options::Day { day, save: _ } => {
+            Options::Day { day, .. } => {
                 assert!(*day <= today.unwrap_or(25), "You can't run a future day!");
                 vec![*day]
             }
@@ -43,17 +54,51 @@ impl Options {
     }
 }
 
+fn timed(run: impl FnOnce() -> (Solution, Solution)) -> ((Solution, Solution), Duration) {
+    let time = Instant::now();
+    let solution = run();
+    (solution, time.elapsed())
+}
+
+/// Solve `day` for the input at `path`, streaming it if it's big and the day can. The time
+/// includes reading the file either way, since a streamed input is only read as it's solved.
+fn run_file(day: u32, path: &Path) -> ((Solution, Solution), Duration) {
+    let size = std::fs::metadata(path)
+        .expect("Could not read from input file")
+        .len();
+
+    let time = Instant::now();
+    if size >= STREAMING_THRESHOLD {
+        let file = File::open(path).expect("Could not read from input file");
+        if let Some(solution) = run_day_streaming(day, BufReader::with_capacity(1 << 20, file)) {
+            let solution = solution.expect("Could not read from input file");
+            let runtime = time.elapsed();
+            println!("Streamed {}", path.display());
+            return (solution, runtime);
+        }
+    }
+
+    let input = std::fs::read_to_string(path).expect("Could not read from input file");
+    let solution = run_day(day, &input);
+    (solution, time.elapsed())
+}
+
 fn main() {
     let options = Options::parse();
     let days = options.days();
 
     let mut total = Duration::ZERO;
     for day in days {
-        let input = get_input(day);
         println!("Day {day}");
-        let time = Instant::now();
-        let (part1, part2) = run_day(day, &input);
-        let runtime = time.elapsed();
+        let ((part1, part2), runtime) = match &options {
+            Options::Day {
+                input: Some(path), ..
+            } => run_file(day, path),
+            _ => {
+                let input = get_input(day);
+                timed(|| run_day(day, &input))
+            }
+        };
         println!("Part 1: {part1}");
         println!("Part 2: {part2}");
         println!("Ran in {runtime:?}");
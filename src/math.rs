@@ -0,0 +1,316 @@
+//! Number theory: greatest common divisors, modular arithmetic and the Chinese remainder theorem.
+
+use std::{
+    fmt,
+    ops::{Add, Div, Mul, Neg, Rem, Sub},
+};
+
+/// Greatest common divisor. For signed types the sign follows the arguments, so take the absolute
+/// value if that matters.
+pub fn gcd<T>(a: T, b: T) -> T
+where
+    T: Copy + PartialEq + Rem<Output = T> + From<u8>,
+{
+    if b == T::from(0) {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Lowest common multiple, or 0 if either is 0
+pub fn lcm<T>(a: T, b: T) -> T
+where
+    T: Copy + PartialEq + Rem<Output = T> + Div<Output = T> + Mul<Output = T> + From<u8>,
+{
+    if a == T::from(0) || b == T::from(0) {
+        return T::from(0);
+    }
+    a / gcd(a, b) * b
+}
+
+/// [`gcd`] of every number, or 0 if there are none
+pub fn gcd_of<T>(numbers: impl IntoIterator<Item = T>) -> T
+where
+    T: Copy + PartialEq + Rem<Output = T> + From<u8>,
+{
+    numbers.into_iter().fold(T::from(0), gcd)
+}
+
+/// [`lcm`] of every number, or 1 if there are none
+pub fn lcm_of<T>(numbers: impl IntoIterator<Item = T>) -> T
+where
+    T: Copy + PartialEq + Rem<Output = T> + Div<Output = T> + Mul<Output = T> + From<u8>,
+{
+    numbers.into_iter().fold(T::from(1), lcm)
+}
+
+/// `a / b` rounded up, or `None` if `b` is 0
+pub fn checked_div_ceil(a: usize, b: usize) -> Option<usize> {
+    Some(a.checked_div(b)? + !a.is_multiple_of(b) as usize)
+}
+
+/// `(g, x, y)` such that `a * x + b * y = g = gcd(a, b)`
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - a / b * y)
+    }
+}
+
+/// The `(residue, modulus)` pair describing every number congruent to each `residue` modulo
+/// its `modulus`, or `None` if there's no such number. The moduli needn't be coprime.
+pub fn crt(congruences: impl IntoIterator<Item = (i64, i64)>) -> Option<(i64, i64)> {
+    let (residue, modulus) =
+        congruences
+            .into_iter()
+            .try_fold((0i128, 1i128), |(r1, m1), (r2, m2)| {
+                let (r2, m2) = (r2 as i128, m2 as i128);
+                let (g, x, _) = extended_gcd(m1, m2);
+                if (r2 - r1) % g != 0 {
+                    return None;
+                }
+
+                let modulus = m1 / g * m2;
+                let step = (r2 - r1) / g * x % (m2 / g);
+                Some(((r1 + m1 * step).rem_euclid(modulus), modulus))
+            })?;
+
+    Some((residue as i64, modulus as i64))
+}
+
+/// An integer modulo `M`, fixed at compile time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Mod<const M: u64>(u64);
+
+impl<const M: u64> Mod<M> {
+    pub fn new(value: u64) -> Self {
+        Self(value % M)
+    }
+
+    pub fn value(self) -> u64 {
+        self.0
+    }
+
+    pub fn pow(self, exponent: u64) -> Self {
+        Self(pow_mod(self.0, exponent, M))
+    }
+
+    /// The number this one has to be multiplied by to get 1, if there is one
+    pub fn inverse(self) -> Option<Self> {
+        inverse_mod(self.0, M).map(Self)
+    }
+}
+
+impl<const M: u64> fmt::Display for Mod<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<const M: u64> Add for Mod<M> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(add_mod(self.0, rhs.0, M))
+    }
+}
+
+impl<const M: u64> Sub for Mod<M> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl<const M: u64> Neg for Mod<M> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self((M - self.0) % M)
+    }
+}
+
+impl<const M: u64> Mul for Mod<M> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self(mul_mod(self.0, rhs.0, M))
+    }
+}
+
+/// An integer modulo a modulus only known at runtime. Combining two numbers with different moduli
+/// is a bug.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DynMod {
+    value: u64,
+    modulus: u64,
+}
+
+impl DynMod {
+    pub fn new(value: u64, modulus: u64) -> Self {
+        Self {
+            value: value % modulus,
+            modulus,
+        }
+    }
+
+    pub fn value(self) -> u64 {
+        self.value
+    }
+
+    pub fn modulus(self) -> u64 {
+        self.modulus
+    }
+
+    pub fn pow(self, exponent: u64) -> Self {
+        Self {
+            value: pow_mod(self.value, exponent, self.modulus),
+            ..self
+        }
+    }
+
+    /// The number this one has to be multiplied by to get 1, if there is one
+    pub fn inverse(self) -> Option<Self> {
+        inverse_mod(self.value, self.modulus).map(|value| Self { value, ..self })
+    }
+
+    fn with_value(self, rhs: Self, value: impl FnOnce(u64, u64, u64) -> u64) -> Self {
+        debug_assert_eq!(self.modulus, rhs.modulus, "mismatched moduli");
+        Self {
+            value: value(self.value, rhs.value, self.modulus),
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for DynMod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl Add for DynMod {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        self.with_value(rhs, add_mod)
+    }
+}
+
+/// Adding a plain number, reduced by the same modulus
+impl Add<u64> for DynMod {
+    type Output = Self;
+
+    fn add(self, rhs: u64) -> Self {
+        self + Self::new(rhs, self.modulus)
+    }
+}
+
+impl Sub for DynMod {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl Neg for DynMod {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            value: (self.modulus - self.value) % self.modulus,
+            ..self
+        }
+    }
+}
+
+impl Mul for DynMod {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        self.with_value(rhs, mul_mod)
+    }
+}
+
+fn add_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    ((a as u128 + b as u128) % modulus as u128) as u64
+}
+
+fn mul_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    ((a as u128 * b as u128) % modulus as u128) as u64
+}
+
+fn pow_mod(mut base: u64, mut exponent: u64, modulus: u64) -> u64 {
+    let mut result = 1 % modulus;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, modulus);
+        }
+        base = mul_mod(base, base, modulus);
+        exponent >>= 1;
+    }
+    result
+}
+
+fn inverse_mod(value: u64, modulus: u64) -> Option<u64> {
+    let (g, x, _) = extended_gcd(value as i128, modulus as i128);
+    (g == 1).then(|| x.rem_euclid(modulus as i128) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn divisors_and_multiples() {
+        assert_eq!(gcd(12usize, 18), 6);
+        assert_eq!(gcd(0usize, 7), 7);
+        assert_eq!(lcm(4u64, 6), 12);
+        assert_eq!(lcm(0u64, 6), 0);
+        assert_eq!(lcm_of([23usize, 19, 13, 17]), 96577);
+        assert_eq!(lcm_of([4usize, 6, 10]), 60);
+        assert_eq!(gcd_of([12i64, -18, 30]).abs(), 6);
+        assert_eq!(lcm_of(Vec::<usize>::new()), 1);
+
+        assert_eq!(checked_div_ceil(7, 2), Some(4));
+        assert_eq!(checked_div_ceil(8, 2), Some(4));
+        assert_eq!(checked_div_ceil(0, 3), Some(0));
+        assert_eq!(checked_div_ceil(1, 0), None);
+    }
+
+    #[test]
+    fn chinese_remainders() {
+        assert_eq!(crt([(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        // Moduli sharing a factor, consistent and not
+        assert_eq!(crt([(3, 4), (5, 6)]), Some((11, 12)));
+        assert_eq!(crt([(0, 4), (1, 6)]), None);
+        assert_eq!(crt([]), Some((0, 1)));
+    }
+
+    #[test]
+    fn modular_arithmetic() {
+        const P: u64 = 1_000_000_007;
+
+        let big = Mod::<P>::new(P - 1);
+        assert_eq!((big * big).value(), 1);
+        assert_eq!((big + Mod::new(5)).value(), 4);
+        assert_eq!((Mod::<P>::new(3) - Mod::new(5)).value(), P - 2);
+        assert_eq!(Mod::<P>::new(2).pow(P - 1).value(), 1);
+        assert_eq!(
+            (Mod::<P>::new(3).inverse().unwrap() * Mod::new(3)).value(),
+            1
+        );
+        assert_eq!(Mod::<12>::new(4).inverse(), None);
+
+        // Products that would overflow a u64
+        let huge = DynMod::new(u64::MAX - 1, u64::MAX);
+        assert_eq!((huge * huge).value(), 1);
+        assert_eq!((DynMod::new(10, 12) + 5).value(), 3);
+        assert_eq!((-DynMod::new(0, 12)).value(), 0);
+    }
+}
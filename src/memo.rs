@@ -0,0 +1,249 @@
+//! Memoization for recursive searches, backed either by a flat table indexed by a packed key or by
+//! a hash map, and keeping count of how well it's doing.
+
+use std::{fmt, hash::Hash, mem::size_of};
+
+use rustc_hash::FxHashMap as HashMap;
+
+/// Environment variable which, when set, makes `Memo::report` print its statistics
+pub const MEMO_STATS_VAR: &str = "AOC_MEMO_STATS";
+
+/// Packs keys into dense indices, for keys whose every field has a small known range
+pub trait KeyEncoder {
+    type Key;
+
+    /// One more than the largest index `encode` can return
+    fn bound(&self) -> usize;
+
+    fn encode(&self, key: &Self::Key) -> usize;
+}
+
+/// Somewhere to keep memoized values
+pub trait Store {
+    type Key;
+    type Value;
+
+    fn get(&self, key: &Self::Key) -> Option<&Self::Value>;
+    fn insert(&mut self, key: Self::Key, value: Self::Value);
+    /// The number of values stored
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Roughly how many bytes the store has allocated
+    fn memory(&self) -> usize;
+}
+
+/// A table with a slot for every key, allocated up front
+pub struct Dense<E, V> {
+    encoder: E,
+    values: Vec<Option<V>>,
+    len: usize,
+}
+
+impl<E: KeyEncoder, V: Clone> Dense<E, V> {
+    pub fn new(encoder: E) -> Self {
+        Self {
+            values: vec![None; encoder.bound()],
+            encoder,
+            len: 0,
+        }
+    }
+}
+
+impl<E: KeyEncoder, V> Store for Dense<E, V> {
+    type Key = E::Key;
+    type Value = V;
+
+    fn get(&self, key: &E::Key) -> Option<&V> {
+        self.values[self.encoder.encode(key)].as_ref()
+    }
+
+    fn insert(&mut self, key: E::Key, value: V) {
+        let slot = &mut self.values[self.encoder.encode(&key)];
+        self.len += slot.is_none() as usize;
+        *slot = Some(value);
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn memory(&self) -> usize {
+        self.values.capacity() * size_of::<Option<V>>()
+    }
+}
+
+/// A hash map, for keys too sparse to give every one of them a slot
+pub struct Hashed<K, V> {
+    values: HashMap<K, V>,
+}
+
+impl<K, V> Default for Hashed<K, V> {
+    fn default() -> Self {
+        Self {
+            values: HashMap::default(),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V> Store for Hashed<K, V> {
+    type Key = K;
+    type Value = V;
+
+    fn get(&self, key: &K) -> Option<&V> {
+        self.values.get(key)
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        self.values.insert(key, value);
+    }
+
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    fn memory(&self) -> usize {
+        // One control byte per bucket on top of the entry itself
+        self.values.capacity() * (size_of::<(K, V)>() + 1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoStats {
+    pub hits: usize,
+    pub misses: usize,
+    pub entries: usize,
+    pub memory: usize,
+}
+
+impl fmt::Display for MemoStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lookups = (self.hits + self.misses).max(1);
+        write!(
+            f,
+            "{} hits, {} misses ({:.1}% hit rate), {} entries in {} KiB",
+            self.hits,
+            self.misses,
+            100.0 * self.hits as f64 / lookups as f64,
+            self.entries,
+            self.memory / 1024,
+        )
+    }
+}
+
+/// A memoization cache that counts its hits and misses
+pub struct Memo<S> {
+    store: S,
+    hits: usize,
+    misses: usize,
+}
+
+impl<E: KeyEncoder, V: Clone> Memo<Dense<E, V>> {
+    pub fn dense(encoder: E) -> Self {
+        Self::new(Dense::new(encoder))
+    }
+}
+
+impl<K: Hash + Eq, V: Clone> Memo<Hashed<K, V>> {
+    pub fn hashed() -> Self {
+        Self::new(Hashed::default())
+    }
+}
+
+impl<S: Store> Memo<S>
+where
+    S::Value: Clone,
+{
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn get(&mut self, key: &S::Key) -> Option<S::Value> {
+        let value = self.store.get(key).cloned();
+        match value {
+            Some(_) => self.hits += 1,
+            None => self.misses += 1,
+        }
+        value
+    }
+
+    pub fn insert(&mut self, key: S::Key, value: S::Value) {
+        self.store.insert(key, value);
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        MemoStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.store.len(),
+            memory: self.store.memory(),
+        }
+    }
+
+    /// Print the cache's statistics to stderr if `AOC_MEMO_STATS` is set
+    pub fn report(&self, name: &str) {
+        if std::env::var_os(MEMO_STATS_VAR).is_some() {
+            eprintln!("{name}: {}", self.stats());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Packs a pair of numbers below 10 into a single digit pair
+    struct Digits;
+
+    impl KeyEncoder for Digits {
+        type Key = (u8, u8);
+
+        fn bound(&self) -> usize {
+            100
+        }
+
+        fn encode(&self, &(tens, units): &(u8, u8)) -> usize {
+            10 * tens as usize + units as usize
+        }
+    }
+
+    /// Number of monotone lattice paths from `(x, y)` to `(0, 0)`
+    fn paths<S: Store<Key = (u8, u8), Value = u64>>(memo: &mut Memo<S>, x: u8, y: u8) -> u64 {
+        if x == 0 || y == 0 {
+            return 1;
+        }
+        if let Some(paths) = memo.get(&(x, y)) {
+            return paths;
+        }
+
+        let result = paths(memo, x - 1, y) + paths(memo, x, y - 1);
+        memo.insert((x, y), result);
+        result
+    }
+
+    #[test]
+    fn backends_agree() {
+        let mut dense = Memo::dense(Digits);
+        let mut hashed = Memo::hashed();
+
+        assert_eq!(paths(&mut dense, 9, 9), 48620);
+        assert_eq!(paths(&mut hashed, 9, 9), 48620);
+        assert_eq!(dense.stats().entries, 81);
+        assert_eq!(
+            (dense.stats().hits, dense.stats().misses),
+            (hashed.stats().hits, hashed.stats().misses)
+        );
+        assert_eq!(dense.stats().memory, 100 * size_of::<Option<u64>>());
+
+        // The second time round, the answer comes straight from the cache
+        let before = dense.stats();
+        paths(&mut dense, 9, 9);
+        assert_eq!(dense.stats().hits, before.hits + 1);
+        assert_eq!(dense.stats().misses, before.misses);
+    }
+}
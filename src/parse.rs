@@ -0,0 +1,290 @@
+//! Integer parsers, both nom combinators and fast loops over bytes, with errors that can point at
+//! exactly where in the input they went wrong.
+
+use std::fmt;
+
+use nom::{combinator::map, IResult};
+
+pub fn parse_usize(input: &str) -> IResult<&str, usize> {
+    map(nom::character::complete::u64, |n| n as usize)(input)
+}
+
+pub fn parse_isize(input: &str) -> IResult<&str, isize> {
+    map(nom::character::complete::i64, |n| n as isize)(input)
+}
+
+/// The value a nom parser produced, ignoring whatever input it didn't need
+pub fn finish<'a, T>(result: IResult<&'a str, T>) -> Result<T, ParseError<'a>> {
+    result.map(|(_, value)| value).map_err(ParseError::from)
+}
+
+/// A primitive integer the byte parsers can produce
+pub trait Integer: Copy {
+    const SIGNED: bool;
+    const ZERO: Self;
+
+    /// `self * 10 + digit`, or minus `digit` when building up a negative number
+    fn push_digit(self, digit: u8, negative: bool) -> Option<Self>;
+}
+
+macro_rules! impl_integer {
+    ($($int:ty: $signed:expr),*) => {
+        $(
+            impl Integer for $int {
+                const SIGNED: bool = $signed;
+                const ZERO: Self = 0;
+
+                fn push_digit(self, digit: u8, negative: bool) -> Option<Self> {
+                    let scaled = self.checked_mul(10)?;
+                    if negative {
+                        scaled.checked_sub(digit as Self)
+                    } else {
+                        scaled.checked_add(digit as Self)
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(
+    u8: false, u16: false, u32: false, u64: false, usize: false,
+    i8: true, i16: true, i32: true, i64: true, isize: true
+);
+
+/// The integer at the start of `bytes`, with a leading `-` allowed if `T` is signed, and the
+/// bytes after it
+fn leading_int<T: Integer>(bytes: &[u8]) -> Result<(&[u8], T), ParseError<'_>> {
+    let negative = T::SIGNED && bytes.first() == Some(&b'-');
+    let unsigned = &bytes[negative as usize..];
+
+    let length = unsigned
+        .iter()
+        .take_while(|byte| byte.is_ascii_digit())
+        .count();
+    if length == 0 {
+        return Err(ParseError::new(unsigned, ParseErrorKind::ExpectedDigit));
+    }
+
+    let (digits, rest) = unsigned.split_at(length);
+    let mut number = T::ZERO;
+    for &digit in digits {
+        number = number
+            .push_digit(digit - b'0', negative)
+            .ok_or(ParseError::new(bytes, ParseErrorKind::TooLarge))?;
+    }
+
+    Ok((rest, number))
+}
+
+/// The integer at the start of `bytes`, which has to be followed by `delimiter`, and the bytes
+/// after the delimiter
+pub fn int_until<T: Integer>(bytes: &[u8], delimiter: u8) -> Result<(&[u8], T), ParseError<'_>> {
+    let (rest, number) = leading_int(bytes)?;
+    match rest.split_first() {
+        Some((&byte, rest)) if byte == delimiter => Ok((rest, number)),
+        _ => Err(ParseError::new(rest, ParseErrorKind::Expected(delimiter))),
+    }
+}
+
+/// The bytes after `prefix`, which `bytes` has to start with
+pub fn tag<'a>(bytes: &'a [u8], prefix: &'static str) -> Result<&'a [u8], ParseError<'a>> {
+    bytes
+        .strip_prefix(prefix.as_bytes())
+        .ok_or(ParseError::new(bytes, ParseErrorKind::ExpectedText(prefix)))
+}
+
+/// `bytes` as an integer, with nothing else around it
+pub fn int_exact<T: Integer>(bytes: &[u8]) -> Result<T, ParseError<'_>> {
+    match leading_int(bytes)? {
+        ([], number) => Ok(number),
+        (rest, _) => Err(ParseError::new(rest, ParseErrorKind::TrailingInput)),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    ExpectedDigit,
+    Expected(u8),
+    ExpectedText(&'static str),
+    TrailingInput,
+    TooLarge,
+    /// Well-formed, but not a value that makes sense here
+    Invalid(&'static str),
+    /// The input ended partway through something
+    Incomplete(&'static str),
+    Nom(nom::error::ErrorKind),
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::ExpectedDigit => write!(f, "expected a digit"),
+            ParseErrorKind::Expected(byte) => write!(f, "expected {:?}", *byte as char),
+            ParseErrorKind::ExpectedText(text) => write!(f, "expected {text:?}"),
+            ParseErrorKind::TrailingInput => write!(f, "unexpected trailing input"),
+            ParseErrorKind::TooLarge => write!(f, "number doesn't fit"),
+            ParseErrorKind::Invalid(what) => write!(f, "invalid {what}"),
+            ParseErrorKind::Incomplete(what) => {
+                write!(f, "incomplete {what} at the end of the input")
+            }
+            ParseErrorKind::Nom(kind) => write!(f, "failed to parse ({})", kind.description()),
+        }
+    }
+}
+
+/// What went wrong, and the input left from where it went wrong
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError<'a> {
+    pub kind: ParseErrorKind,
+    at: &'a [u8],
+}
+
+impl<'a> ParseError<'a> {
+    pub fn new(at: &'a [u8], kind: ParseErrorKind) -> Self {
+        Self { kind, at }
+    }
+
+    /// The error with the line of `input` it happened on, with a caret under the column. `input`
+    /// has to contain the slice that was being parsed, or there's no line to show.
+    pub fn render(&self, input: &str) -> String {
+        let input = input.as_bytes();
+        let Some(offset) = self.offset_in(input) else {
+            return self.to_string();
+        };
+
+        let line_start = input[..offset]
+            .iter()
+            .rposition(|&byte| byte == b'\n')
+            .map_or(0, |newline| newline + 1);
+        let line_end = input[offset..]
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .map_or(input.len(), |newline| offset + newline);
+
+        let line_number = input[..line_start]
+            .iter()
+            .filter(|&&byte| byte == b'\n')
+            .count()
+            + 1;
+        self.render_line(&input[line_start..line_end], line_number)
+    }
+
+    /// Like [`render`](Self::render), for when only the line the error happened on is at hand
+    pub fn render_line(&self, line: &[u8], line_number: usize) -> String {
+        let Some(offset) = self.offset_in(line) else {
+            return self.to_string();
+        };
+
+        let text = String::from_utf8_lossy(line);
+        let text = text.trim_end_matches('\r');
+        let column = offset + 1;
+        let gutter = " ".repeat(line_number.to_string().len());
+
+        format!(
+            "{self} at line {line_number}, column {column}\n\
+             {gutter} |\n\
+             {line_number} | {text}\n\
+             {gutter} | {caret:>column$}",
+            caret = "^",
+        )
+    }
+
+    /// Give up on solving, showing where `input` is broken
+    pub fn report(self, input: &str) -> ! {
+        panic!("{}", self.render(input))
+    }
+
+    /// Give up on solving, showing where line `line_number` is broken
+    pub fn report_line(self, line: &[u8], line_number: usize) -> ! {
+        panic!("{}", self.render_line(line, line_number))
+    }
+
+    /// Where the error happened in `input`, if it's part of it
+    fn offset_in(&self, input: &[u8]) -> Option<usize> {
+        (self.at.as_ptr() as usize)
+            .checked_sub(input.as_ptr() as usize)
+            .filter(|&offset| offset <= input.len())
+    }
+}
+
+impl fmt::Display for ParseError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl std::error::Error for ParseError<'_> {}
+
+impl<'a> From<nom::Err<nom::error::Error<&'a str>>> for ParseError<'a> {
+    fn from(error: nom::Err<nom::error::Error<&'a str>>) -> Self {
+        match error {
+            nom::Err::Error(error) | nom::Err::Failure(error) => {
+                Self::new(error.input.as_bytes(), ParseErrorKind::Nom(error.code))
+            }
+            nom::Err::Incomplete(_) => {
+                Self::new(&[], ParseErrorKind::Nom(nom::error::ErrorKind::Eof))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn byte_integers() {
+        assert_eq!(int_until::<u8>(b"12-34", b'-'), Ok((&b"34"[..], 12)));
+        assert_eq!(int_until::<isize>(b"-7,1", b','), Ok((&b"1"[..], -7)));
+        assert_eq!(int_exact::<i8>(b"-128"), Ok(-128));
+        assert_eq!(int_exact::<usize>(b"0042"), Ok(42));
+
+        let kind = |result: Result<u8, ParseError>| result.unwrap_err().kind;
+        assert_eq!(kind(int_exact(b"-1")), ParseErrorKind::ExpectedDigit);
+        assert_eq!(kind(int_exact(b"")), ParseErrorKind::ExpectedDigit);
+        assert_eq!(kind(int_exact(b"256")), ParseErrorKind::TooLarge);
+        assert_eq!(kind(int_exact(b"25 ")), ParseErrorKind::TrailingInput);
+        assert_eq!(
+            int_until::<u8>(b"12", b'\n').unwrap_err().kind,
+            ParseErrorKind::Expected(b'\n')
+        );
+
+        assert_eq!(tag(b"move 1", "move "), Ok(&b"1"[..]));
+        assert_eq!(
+            tag(b"mov", "move ").unwrap_err().kind,
+            ParseErrorKind::ExpectedText("move ")
+        );
+    }
+
+    #[test]
+    fn points_at_the_error() {
+        let input = "1-2,3-4\n5-6,x-8\n";
+        let line = &input[8..];
+        let (rest, _) = int_until::<u8>(line.as_bytes(), b'-').unwrap();
+        let (rest, _) = int_until::<u8>(rest, b',').unwrap();
+        let error = int_until::<u8>(rest, b'-').unwrap_err();
+
+        assert_eq!(
+            error.render(input),
+            "expected a digit at line 2, column 5\n  |\n2 | 5-6,x-8\n  |     ^"
+        );
+        // Only the line itself is known
+        assert_eq!(
+            error.render(line).lines().next().unwrap(),
+            "expected a digit at line 1, column 5"
+        );
+        assert_eq!(error.render("unrelated"), "expected a digit");
+        assert_eq!(
+            error.render_line(&line.as_bytes()[..7], 12),
+            "expected a digit at line 12, column 5\n   |\n12 | 5-6,x-8\n   |     ^"
+        );
+
+        let input = "10\n20 30\r\n";
+        let error = finish(parse_usize(&input[5..])).unwrap_err();
+        assert_eq!(
+            error.render(input),
+            "failed to parse (Digit) at line 2, column 3\n  |\n2 | 20 30\n  |   ^"
+        );
+    }
+}
@@ -1,5 +1,7 @@
+pub use crate::geom::*;
 pub use crate::grid::*;
-pub use crate::parse_usize;
+pub use crate::interval::IntervalSet;
+pub use crate::parse::{parse_isize, parse_usize};
 pub use crate::Solution;
 pub use itertools::Itertools;
 pub use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
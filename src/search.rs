@@ -0,0 +1,264 @@
+//! Shortest path searches over graphs given either as a [`Graph`] or as a closure listing each
+//! node's neighbours.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, VecDeque},
+    hash::Hash,
+};
+
+use rustc_hash::FxHashMap as HashMap;
+
+/// A cheapest path found by a search, from one of the sources to a goal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<N> {
+    pub cost: usize,
+    /// Every node along the path, starting with the source and ending with the goal
+    pub nodes: Vec<N>,
+}
+
+impl<N> Path<N> {
+    pub fn goal(&self) -> &N {
+        self.nodes.last().unwrap()
+    }
+}
+
+pub trait Graph {
+    type Node: Clone + Eq + Hash;
+
+    /// The nodes one step away from `node`, with the cost of each step
+    fn neighbours(&self, node: &Self::Node) -> Vec<(Self::Node, usize)>;
+
+    /// See [`bfs`]. Step costs are ignored.
+    fn bfs(
+        &self,
+        sources: impl IntoIterator<Item = Self::Node>,
+        is_goal: impl FnMut(&Self::Node) -> bool,
+    ) -> Option<Path<Self::Node>> {
+        bfs(
+            sources,
+            |node| self.neighbours(node).into_iter().map(|(node, _)| node),
+            is_goal,
+        )
+    }
+
+    /// See [`dijkstra`]
+    fn dijkstra(
+        &self,
+        sources: impl IntoIterator<Item = Self::Node>,
+        is_goal: impl FnMut(&Self::Node) -> bool,
+    ) -> Option<Path<Self::Node>> {
+        dijkstra(sources, |node| self.neighbours(node), is_goal)
+    }
+
+    /// See [`astar`]
+    fn astar(
+        &self,
+        sources: impl IntoIterator<Item = Self::Node>,
+        heuristic: impl FnMut(&Self::Node) -> usize,
+        is_goal: impl FnMut(&Self::Node) -> bool,
+    ) -> Option<Path<Self::Node>> {
+        astar(sources, |node| self.neighbours(node), heuristic, is_goal)
+    }
+}
+
+/// Every node seen so far, each with the cheapest known cost to get there and the node it was
+/// reached from
+struct Visited<N> {
+    nodes: Vec<N>,
+    indices: HashMap<N, usize>,
+    best: Vec<(usize, Option<usize>)>,
+}
+
+impl<N: Clone + Eq + Hash> Visited<N> {
+    fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            indices: HashMap::default(),
+            best: Vec::new(),
+        }
+    }
+
+    /// The index of `node`, and whether it had been seen before
+    fn intern(&mut self, node: N) -> (usize, bool) {
+        if let Some(&index) = self.indices.get(&node) {
+            return (index, true);
+        }
+
+        let index = self.nodes.len();
+        self.indices.insert(node.clone(), index);
+        self.nodes.push(node);
+        self.best.push((usize::MAX, None));
+        (index, false)
+    }
+
+    fn path_to(&self, mut index: usize) -> Path<N> {
+        let cost = self.best[index].0;
+        let mut nodes = vec![self.nodes[index].clone()];
+        while let Some(parent) = self.best[index].1 {
+            nodes.push(self.nodes[parent].clone());
+            index = parent;
+        }
+        nodes.reverse();
+
+        Path { cost, nodes }
+    }
+}
+
+/// Breadth-first search from every source at once, where every step costs 1. Returns the shortest
+/// path to the first goal found, or `None` if no goal can be reached.
+pub fn bfs<N, I>(
+    sources: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut visited = Visited::new();
+    let mut queue = VecDeque::new();
+
+    for source in sources {
+        let (index, seen) = visited.intern(source);
+        if !seen {
+            visited.best[index] = (0, None);
+            queue.push_back(index);
+        }
+    }
+
+    while let Some(index) = queue.pop_front() {
+        let node = visited.nodes[index].clone();
+        if is_goal(&node) {
+            return Some(visited.path_to(index));
+        }
+
+        let cost = visited.best[index].0;
+        for neighbour in neighbours(&node) {
+            let (next, seen) = visited.intern(neighbour);
+            if !seen {
+                visited.best[next] = (cost + 1, Some(index));
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+/// Cheapest path search from every source at once, with the cost of each step given alongside the
+/// neighbours
+pub fn dijkstra<N, I>(
+    sources: impl IntoIterator<Item = N>,
+    neighbours: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    astar(sources, neighbours, |_| 0, is_goal)
+}
+
+/// Cheapest path search guided by `heuristic`, which must never overestimate the remaining cost
+/// to the nearest goal
+pub fn astar<N, I>(
+    sources: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> usize,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    let mut visited = Visited::new();
+    let mut heap = BinaryHeap::new();
+
+    for source in sources {
+        let (index, _) = visited.intern(source);
+        visited.best[index] = (0, None);
+        heap.push(Reverse((heuristic(&visited.nodes[index]), 0, index)));
+    }
+
+    while let Some(Reverse((_, cost, index))) = heap.pop() {
+        if cost > visited.best[index].0 {
+            // Already reached more cheaply
+            continue;
+        }
+
+        let node = visited.nodes[index].clone();
+        if is_goal(&node) {
+            return Some(visited.path_to(index));
+        }
+
+        for (neighbour, step) in neighbours(&node) {
+            let next_cost = cost + step;
+            let (next, _) = visited.intern(neighbour);
+            if next_cost < visited.best[next].0 {
+                visited.best[next] = (next_cost, Some(index));
+                let estimate = next_cost + heuristic(&visited.nodes[next]);
+                heap.push(Reverse((estimate, next_cost, next)));
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A line of nodes `0..10`, where stepping forward costs 1 and jumping ahead by 3 costs 2
+    struct Line;
+
+    impl Graph for Line {
+        type Node = u32;
+
+        fn neighbours(&self, &node: &u32) -> Vec<(u32, usize)> {
+            [(node + 1, 1), (node + 3, 2)]
+                .into_iter()
+                .filter(|&(next, _)| next < 10)
+                .collect()
+        }
+    }
+
+    #[test]
+    fn searches_agree() {
+        let bfs = Line.bfs([0], |&node| node == 9).unwrap();
+        assert_eq!(bfs.cost, 3);
+        assert_eq!(bfs.nodes, [0, 3, 6, 9]);
+
+        let dijkstra = Line.dijkstra([0], |&node| node == 8).unwrap();
+        assert_eq!(dijkstra.cost, 6);
+        assert_eq!(*dijkstra.goal(), 8);
+
+        let astar = Line.astar(
+            [0],
+            |&node| (8 - node.min(8)) as usize / 2,
+            |&node| node == 8,
+        );
+        assert_eq!(astar.unwrap().cost, 6);
+    }
+
+    #[test]
+    fn multiple_sources() {
+        let path = Line.dijkstra([0, 7], |&node| node == 9).unwrap();
+        assert_eq!(path.cost, 2);
+        assert_eq!(path.nodes[0], 7);
+    }
+
+    #[test]
+    fn unreachable() {
+        assert_eq!(Line.bfs([5], |&node| node == 2), None);
+        assert_eq!(
+            bfs(
+                [0u32],
+                |&node| [node + 1].into_iter().filter(|&n| n < 4),
+                |_| false
+            ),
+            None
+        );
+    }
+}
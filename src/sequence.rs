@@ -0,0 +1,251 @@
+//! A list that can find where any of its elements currently is, and move elements around, in
+//! logarithmic time.
+
+/// Identifies an element of a [`Sequence`] for as long as it lives, wherever it moves to. Handles
+/// count up from 0 in the order elements are pushed.
+pub type Handle = usize;
+
+#[derive(Debug, Clone)]
+struct Node<T> {
+    value: T,
+    priority: u64,
+    left: Option<Handle>,
+    right: Option<Handle>,
+    parent: Option<Handle>,
+    /// Number of nodes in the subtree rooted here
+    size: usize,
+}
+
+/// An implicit treap: a binary tree ordered by position in the list, kept balanced by giving each
+/// node a random priority, and with parent links so an element's position can be worked out from
+/// its handle
+#[derive(Debug, Clone)]
+pub struct Sequence<T> {
+    nodes: Vec<Node<T>>,
+    root: Option<Handle>,
+    /// State of the xorshift generator for priorities
+    seed: u64,
+}
+
+impl<T> Default for Sequence<T> {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            root: None,
+            seed: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+}
+
+impl<T> Sequence<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.size(self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Add `value` to the end of the list
+    pub fn push(&mut self, value: T) -> Handle {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+
+        let handle = self.nodes.len();
+        self.nodes.push(Node {
+            value,
+            priority: self.seed,
+            left: None,
+            right: None,
+            parent: None,
+            size: 1,
+        });
+
+        self.root = self.merge(self.root, Some(handle));
+        self.detach(self.root);
+        handle
+    }
+
+    pub fn value(&self, handle: Handle) -> &T {
+        &self.nodes[handle].value
+    }
+
+    /// The element's current position in the list
+    pub fn index_of(&self, handle: Handle) -> usize {
+        let mut index = self.size(self.nodes[handle].left);
+
+        let mut child = handle;
+        while let Some(parent) = self.nodes[child].parent {
+            if self.nodes[parent].right == Some(child) {
+                index += self.size(self.nodes[parent].left) + 1;
+            }
+            child = parent;
+        }
+
+        index
+    }
+
+    /// The element at position `index`
+    pub fn handle_at(&self, mut index: usize) -> Option<Handle> {
+        let mut node = self.root?;
+        loop {
+            let left = self.size(self.nodes[node].left);
+            match index.cmp(&left) {
+                std::cmp::Ordering::Less => node = self.nodes[node].left?,
+                std::cmp::Ordering::Equal => return Some(node),
+                std::cmp::Ordering::Greater => {
+                    index -= left + 1;
+                    node = self.nodes[node].right?;
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.handle_at(index).map(|handle| self.value(handle))
+    }
+
+    /// Take the element out of the list and put it back so that it ends up at position `index`
+    pub fn move_to(&mut self, handle: Handle, index: usize) {
+        let (before, rest) = self.split(self.root, self.index_of(handle));
+        let (_, after) = self.split(rest, 1);
+        let rest = self.merge(before, after);
+
+        let (before, after) = self.split(rest, index);
+        let before = self.merge(before, Some(handle));
+        self.root = self.merge(before, after);
+        self.detach(self.root);
+    }
+
+    /// Move the element `offset` places along, treating the list as a circle. Moving an element
+    /// all the way round the rest of the list leaves it where it was.
+    pub fn move_by(&mut self, handle: Handle, offset: isize) {
+        let others = self.len() as isize - 1;
+        if others == 0 {
+            return;
+        }
+
+        let index = (self.index_of(handle) as isize + offset).rem_euclid(others);
+        self.move_to(handle, index as usize);
+    }
+
+    /// The elements in order
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        (0..self.len()).map(|index| self.get(index).unwrap())
+    }
+
+    fn size(&self, node: Option<Handle>) -> usize {
+        node.map_or(0, |node| self.nodes[node].size)
+    }
+
+    fn detach(&mut self, node: Option<Handle>) {
+        if let Some(node) = node {
+            self.nodes[node].parent = None;
+        }
+    }
+
+    /// Recompute the node's size from its children, and point them back at it
+    fn update(&mut self, node: Handle) {
+        let (left, right) = (self.nodes[node].left, self.nodes[node].right);
+        self.nodes[node].size = 1 + self.size(left) + self.size(right);
+        for child in [left, right].into_iter().flatten() {
+            self.nodes[child].parent = Some(node);
+        }
+    }
+
+    /// Join two trees, with every element of `left` coming before every element of `right`. The
+    /// root returned may still have a stale parent link.
+    fn merge(&mut self, left: Option<Handle>, right: Option<Handle>) -> Option<Handle> {
+        let (Some(l), Some(r)) = (left, right) else {
+            return left.or(right);
+        };
+
+        if self.nodes[l].priority > self.nodes[r].priority {
+            self.nodes[l].right = self.merge(self.nodes[l].right, Some(r));
+            self.update(l);
+            Some(l)
+        } else {
+            self.nodes[r].left = self.merge(Some(l), self.nodes[r].left);
+            self.update(r);
+            Some(r)
+        }
+    }
+
+    /// Split a tree into its first `count` elements and the rest. The roots returned may still
+    /// have stale parent links.
+    fn split(&mut self, node: Option<Handle>, count: usize) -> (Option<Handle>, Option<Handle>) {
+        let Some(node) = node else {
+            return (None, None);
+        };
+
+        let left = self.size(self.nodes[node].left);
+        if count <= left {
+            let (before, after) = self.split(self.nodes[node].left, count);
+            self.nodes[node].left = after;
+            self.update(node);
+            (before, Some(node))
+        } else {
+            let (before, after) = self.split(self.nodes[node].right, count - left - 1);
+            self.nodes[node].right = before;
+            self.update(node);
+            (Some(node), after)
+        }
+    }
+}
+
+impl<T> FromIterator<T> for Sequence<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(values: I) -> Self {
+        let mut sequence = Self::new();
+        for value in values {
+            sequence.push(value);
+        }
+        sequence
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    #[test]
+    fn moves_match_a_vec() {
+        let mut sequence: Sequence<usize> = (0..50).collect();
+        let mut expected = (0..50).collect_vec();
+
+        let mut offset = 7isize;
+        for handle in (0..50).cycle().take(400) {
+            offset = (offset * 31 + 11) % 97 - 48;
+            sequence.move_by(handle, offset);
+
+            let from = expected.iter().position(|&value| value == handle).unwrap();
+            let to = (from as isize + offset).rem_euclid(49) as usize;
+            let value = expected.remove(from);
+            expected.insert(to, value);
+
+            assert_eq!(sequence.index_of(handle), to);
+        }
+
+        assert_eq!(sequence.iter().copied().collect_vec(), expected);
+        assert_eq!(sequence.len(), 50);
+        assert_eq!(sequence.get(50), None);
+    }
+
+    #[test]
+    fn handles_follow_their_elements() {
+        let mut sequence: Sequence<char> = "abcde".chars().collect();
+        sequence.move_to(0, 4);
+        sequence.move_to(4, 0);
+
+        assert_eq!(sequence.iter().collect::<String>(), "ebcda");
+        assert_eq!(*sequence.value(4), 'e');
+        assert_eq!(sequence.index_of(0), 4);
+        assert_eq!(sequence.handle_at(1), Some(1));
+    }
+}